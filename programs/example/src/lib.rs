@@ -1,11 +1,11 @@
 #![allow(unexpected_cfgs)]
+#![allow(clippy::too_many_arguments)]
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
     token::{Mint, Token, TokenAccount},
 };
 
-use streamflow_sdk;
 use streamflow_sdk::cpi::accounts::{
     Create as CpiCreate,
     CreateUnchecked as CpiCreateUnchecked,
@@ -16,6 +16,8 @@ use streamflow_sdk::cpi::accounts::{
     Transfer as CpiTransfer,
     Cancel as CpiCancel,
 };
+use streamflow_sdk::account::StreamMetadata;
+use streamflow_sdk::Streamflow;
 
 declare_id!("FGjLaVo5zLGdzCxMo9gu9tXr1kzTToKd8C8K7YS5hNM1");
 
@@ -506,8 +508,8 @@ pub struct Create<'info> {
     /// CHECK: Internal program that handles fees for specified partners.
     pub fee_oracle: UncheckedAccount<'info>,
     pub rent: Sysvar<'info, Rent>,
-    /// CHECK: Streamflow protocol (alias timelock) program account.
-    pub streamflow_program: UncheckedAccount<'info>,
+    /// Streamflow protocol (alias timelock) program account.
+    pub streamflow_program: Program<'info, Streamflow>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
@@ -535,8 +537,8 @@ pub struct CreateUnchecked<'info> {
     /// CHECK: Internal program that handles fees for specified partners.
     pub fee_oracle: UncheckedAccount<'info>,
     pub rent: Sysvar<'info, Rent>,
-    /// CHECK: Streamflow protocol (alias timelock) program account.
-    pub streamflow_program: UncheckedAccount<'info>,
+    /// Streamflow protocol (alias timelock) program account.
+    pub streamflow_program: Program<'info, Streamflow>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
@@ -565,8 +567,8 @@ pub struct CreateUncheckedWithPayer<'info> {
     /// CHECK: Internal program that handles fees for specified partners.
     pub fee_oracle: UncheckedAccount<'info>,
     pub rent: Sysvar<'info, Rent>,
-    /// CHECK: Streamflow protocol (alias timelock) program account.
-    pub streamflow_program: UncheckedAccount<'info>,
+    /// Streamflow protocol (alias timelock) program account.
+    pub streamflow_program: Program<'info, Streamflow>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
@@ -581,8 +583,8 @@ pub struct Update<'info> {
     #[account(mut)]
     /// CHECK: Delegate account for automatically withdrawing contracts.
     pub withdrawor: UncheckedAccount<'info>,
-    /// CHECK: Streamflow protocol (alias timelock) program account.
-    pub streamflow_program: UncheckedAccount<'info>,
+    /// Streamflow protocol (alias timelock) program account.
+    pub streamflow_program: Program<'info, Streamflow>,
     pub system_program: Program<'info, System>,
 }
 
@@ -598,9 +600,9 @@ pub struct Withdraw<'info> {
         associated_token::authority = recipient,
     )]
     pub recipient_tokens: Box<Account<'info, TokenAccount>>,
-    #[account(mut)]
-    /// CHECK: The account holding the vesting parameters.
-    pub metadata: UncheckedAccount<'info>,
+    #[account(mut, has_one = recipient, has_one = escrow_tokens, has_one = mint)]
+    /// The account holding the vesting parameters.
+    pub metadata: Box<Account<'info, StreamMetadata>>,
     #[account(mut)]
     /// CHECK: The escrow account holding the funds
     pub escrow_tokens: UncheckedAccount<'info>,
@@ -623,8 +625,8 @@ pub struct Withdraw<'info> {
     pub partner_tokens: Box<Account<'info, TokenAccount>>,
     #[account(mut)]
     pub mint:Box<Account<'info, Mint>>,
-    /// CHECK: Streamflow protocol (alias timelock) program account.
-    pub streamflow_program: UncheckedAccount<'info>,
+    /// Streamflow protocol (alias timelock) program account.
+    pub streamflow_program: Program<'info, Streamflow>,
     pub token_program: Program<'info, Token>,
 }
 
@@ -648,9 +650,9 @@ pub struct Cancel<'info> {
         associated_token::authority = recipient,
     )]
     pub recipient_tokens: Box<Account<'info, TokenAccount>>,
-    #[account(mut)]
-    /// CHECK: The account holding the vesting parameters.
-    pub metadata: UncheckedAccount<'info>,
+    #[account(mut, has_one = sender, has_one = recipient, has_one = escrow_tokens, has_one = mint)]
+    /// The account holding the vesting parameters.
+    pub metadata: Box<Account<'info, StreamMetadata>>,
     #[account(mut)]
     /// CHECK: The escrow account holding the funds
     pub escrow_tokens: UncheckedAccount<'info>,
@@ -673,8 +675,8 @@ pub struct Cancel<'info> {
     pub partner_tokens: Box<Account<'info, TokenAccount>>,
     #[account(mut)]
     pub mint: Box<Account<'info, Mint>>,
-    /// CHECK: Streamflow protocol (alias timelock) program account.
-    pub streamflow_program: UncheckedAccount<'info>,
+    /// Streamflow protocol (alias timelock) program account.
+    pub streamflow_program: Program<'info, Streamflow>,
     pub token_program: Program<'info, Token>,
 }
 
@@ -697,8 +699,8 @@ pub struct Transfer<'info> {
     pub metadata: UncheckedAccount<'info>,
     pub mint: Box<Account<'info, Mint>>,
     pub rent: Sysvar<'info, Rent>,
-    /// CHECK: Streamflow protocol (alias timelock) program account.
-    pub streamflow_program: UncheckedAccount<'info>,
+    /// Streamflow protocol (alias timelock) program account.
+    pub streamflow_program: Program<'info, Streamflow>,
     pub token_program: Program<'info, Token>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
@@ -713,9 +715,9 @@ pub struct Topup<'info> {
         associated_token::authority = sender,
     )]
     pub sender_tokens: Box<Account<'info, TokenAccount>>,
-    #[account(mut)]
-    /// CHECK: The account holding the vesting parameters.
-    pub metadata: UncheckedAccount<'info>,
+    #[account(mut, has_one = escrow_tokens, has_one = mint)]
+    /// The account holding the vesting parameters.
+    pub metadata: Box<Account<'info, StreamMetadata>>,
     #[account(mut)]
     /// CHECK: The escrow account holding the funds
     pub escrow_tokens: UncheckedAccount<'info>,
@@ -740,8 +742,8 @@ pub struct Topup<'info> {
     )]
     pub partner_tokens: Box<Account<'info, TokenAccount>>,
     pub mint: Box<Account<'info, Mint>>,
-    /// CHECK: Streamflow protocol (alias timelock) program account.
-    pub streamflow_program: UncheckedAccount<'info>,
+    /// Streamflow protocol (alias timelock) program account.
+    pub streamflow_program: Program<'info, Streamflow>,
     pub token_program: Program<'info, Token>,
     pub system_program: Program<'info, System>,
 }
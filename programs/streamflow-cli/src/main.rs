@@ -0,0 +1,601 @@
+//! Command-line tool to inspect Streams and build Streamflow transactions
+//!
+//! Works offline: Streams are read from metadata account data and built transactions are printed
+//! unsigned, so they can be signed elsewhere or proposed to a multisig.
+
+mod output;
+
+use std::path::Path;
+use std::process::ExitCode;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::{Args, Parser, Subcommand};
+use solana_hash::Hash;
+use streamflow_sdk::instructions;
+use streamflow_sdk::state::{
+    derive_metadata, find_escrow_account, pad_stream_name, trim_stream_name, Contract,
+    CreateParams, STRM_TREASURY,
+};
+use streamflow_sdk::update::{update_instruction, UpdateArgs};
+use streamflow_sdk::versioned::VersionedContract;
+
+use crate::output::Format;
+
+type CliResult<T> = std::result::Result<T, String>;
+
+#[derive(Parser)]
+#[command(name = "streamflow-cli", version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the Stream stored in metadata account data as JSON
+    Decode(StreamArgs),
+    /// Print amounts, pause state and the next unlock of a Stream
+    Status(StreamArgs),
+    /// Print upcoming unlocks of a Stream: time, amount and total unlocked after it
+    Schedule(ScheduleArgs),
+    /// Print token transfers a cancel would make
+    PreviewCancel(StreamArgs),
+    /// Derive Stream addresses
+    #[command(subcommand)]
+    Derive(Derive),
+    /// Build an unsigned instruction, message or transaction
+    Build(Box<Build>),
+}
+
+#[derive(Args)]
+struct StreamArgs {
+    /// Base64 metadata account data or a path to a file with raw account data
+    stream: String,
+    /// Unix timestamp to evaluate the Stream at, current time by default
+    #[arg(long)]
+    now: Option<u64>,
+}
+
+#[derive(Args)]
+struct ScheduleArgs {
+    #[command(flatten)]
+    stream: StreamArgs,
+    /// Number of unlocks to print
+    #[arg(long, default_value_t = 12)]
+    limit: usize,
+}
+
+#[derive(Subcommand)]
+enum Derive {
+    /// Metadata PDA of a v2 Stream and its escrow
+    Metadata {
+        #[arg(long)]
+        mint: Pubkey,
+        /// Sender, or payer for `create_unchecked_with_payer_v2`
+        #[arg(long)]
+        payer: Pubkey,
+        #[arg(long)]
+        nonce: u32,
+    },
+    /// Escrow token account of a Stream
+    Escrow {
+        #[arg(long)]
+        metadata: Pubkey,
+    },
+}
+
+#[derive(Args)]
+struct Build {
+    #[arg(long, value_enum, default_value_t, global = true)]
+    format: Format,
+    /// Fee payer of the message, first signer of the instruction by default
+    #[arg(long, global = true)]
+    payer: Option<Pubkey>,
+    /// Recent blockhash of the message, zeroes by default, required for `--format transaction`
+    #[arg(long, global = true)]
+    blockhash: Option<Hash>,
+    #[command(subcommand)]
+    method: Method,
+}
+
+#[derive(Subcommand)]
+enum Method {
+    Create {
+        #[command(flatten)]
+        accounts: CreateAccounts,
+        /// New keypair address of the metadata account
+        #[arg(long)]
+        metadata: Pubkey,
+        #[command(flatten)]
+        params: CreateArgs,
+    },
+    CreateV2 {
+        #[command(flatten)]
+        accounts: CreateAccounts,
+        #[command(flatten)]
+        params: CreateArgs,
+    },
+    CreateUnchecked {
+        #[command(flatten)]
+        accounts: CreateAccounts,
+        /// Pre-initialized metadata account
+        #[arg(long)]
+        metadata: Pubkey,
+        #[command(flatten)]
+        params: CreateArgs,
+    },
+    CreateUncheckedV2 {
+        #[command(flatten)]
+        accounts: CreateAccounts,
+        #[command(flatten)]
+        params: CreateArgs,
+    },
+    CreateUncheckedWithPayer {
+        #[command(flatten)]
+        accounts: CreateAccounts,
+        /// Account paying for the Stream creation
+        #[arg(long = "stream-payer")]
+        stream_payer: Pubkey,
+        /// Pre-initialized metadata account
+        #[arg(long)]
+        metadata: Pubkey,
+        #[command(flatten)]
+        params: CreateArgs,
+    },
+    CreateUncheckedWithPayerV2 {
+        #[command(flatten)]
+        accounts: CreateAccounts,
+        /// Account paying for the Stream creation, metadata PDA is derived from it
+        #[arg(long = "stream-payer")]
+        stream_payer: Pubkey,
+        #[command(flatten)]
+        params: CreateArgs,
+    },
+    Update {
+        #[arg(long)]
+        sender: Pubkey,
+        #[arg(long)]
+        metadata: Pubkey,
+        #[command(flatten)]
+        args: UpdateFlags,
+    },
+    Withdraw {
+        #[command(flatten)]
+        stream: StreamAccount,
+        #[arg(long)]
+        authority: Pubkey,
+        /// Amount to withdraw, everything available by default
+        #[arg(long, default_value_t = u64::MAX)]
+        amount: u64,
+    },
+    Cancel {
+        #[command(flatten)]
+        stream: StreamAccount,
+        #[arg(long)]
+        authority: Pubkey,
+    },
+    Pause {
+        #[arg(long)]
+        sender: Pubkey,
+        #[arg(long)]
+        metadata: Pubkey,
+    },
+    Unpause {
+        #[arg(long)]
+        sender: Pubkey,
+        #[arg(long)]
+        metadata: Pubkey,
+    },
+    TransferRecipient {
+        #[command(flatten)]
+        stream: StreamAccount,
+        #[arg(long)]
+        authority: Pubkey,
+        #[arg(long)]
+        new_recipient: Pubkey,
+    },
+    TransferSender {
+        #[command(flatten)]
+        stream: StreamAccount,
+        #[arg(long)]
+        sender: Pubkey,
+        #[arg(long)]
+        new_sender: Pubkey,
+    },
+    Topup {
+        #[command(flatten)]
+        stream: StreamAccount,
+        #[arg(long)]
+        sender: Pubkey,
+        #[arg(long)]
+        amount: u64,
+    },
+}
+
+#[derive(Args)]
+struct CreateAccounts {
+    #[arg(long)]
+    sender: Pubkey,
+    #[arg(long)]
+    mint: Pubkey,
+    #[arg(long)]
+    recipient: Pubkey,
+    /// Partner receiving the partner fee, Streamflow treasury by default
+    #[arg(long)]
+    partner: Option<Pubkey>,
+}
+
+#[derive(Args)]
+struct CreateArgs {
+    #[arg(long)]
+    start_time: u64,
+    #[arg(long)]
+    net_amount_deposited: u64,
+    #[arg(long)]
+    period: u64,
+    #[arg(long)]
+    amount_per_period: u64,
+    #[arg(long, default_value_t = 0)]
+    cliff: u64,
+    #[arg(long, default_value_t = 0)]
+    cliff_amount: u64,
+    #[arg(long)]
+    cancelable_by_sender: bool,
+    #[arg(long)]
+    cancelable_by_recipient: bool,
+    #[arg(long)]
+    automatic_withdrawal: bool,
+    #[arg(long)]
+    transferable_by_sender: bool,
+    #[arg(long)]
+    transferable_by_recipient: bool,
+    #[arg(long)]
+    can_topup: bool,
+    #[arg(long, default_value = "")]
+    stream_name: String,
+    #[arg(long, default_value_t = 0)]
+    withdraw_frequency: u64,
+    #[arg(long)]
+    pausable: bool,
+    #[arg(long)]
+    can_update_rate: bool,
+    /// Nonce of the metadata PDA, only used by v2 methods
+    #[arg(long, default_value_t = 0)]
+    nonce: u32,
+}
+
+#[derive(Args)]
+struct UpdateFlags {
+    /// Enable automatic withdrawals with the given frequency in seconds
+    #[arg(long)]
+    enable_automatic_withdrawal: Option<u64>,
+    /// New amount unlocked per period
+    #[arg(long)]
+    amount_per_period: Option<u64>,
+    #[arg(long)]
+    disable_sender_transfer: bool,
+    #[arg(long)]
+    enable_recipient_transfer: bool,
+    #[arg(long)]
+    disable_sender_cancel: bool,
+}
+
+#[derive(Args)]
+struct StreamAccount {
+    #[arg(long)]
+    metadata: Pubkey,
+    /// Base64 metadata account data or a path to a file with raw account data
+    #[arg(long)]
+    stream: String,
+}
+
+/// Read metadata account data from a file or decode it from base64
+fn read_stream(input: &str) -> CliResult<VersionedContract> {
+    let data = if Path::new(input).is_file() {
+        std::fs::read(input).map_err(|e| format!("can't read {input}: {e}"))?
+    } else {
+        STANDARD
+            .decode(input.trim())
+            .map_err(|e| format!("stream is neither a file nor base64: {e}"))?
+    };
+    VersionedContract::decode(&data).map_err(|e| format!("invalid stream data: {e}"))
+}
+
+fn now_or_current(now: Option<u64>) -> u64 {
+    now.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    })
+}
+
+/// Stream at `now`, the account doesn't record how tokens unlocked between the start and the last
+/// rate change
+fn stream_at(input: &str, now: Option<u64>) -> CliResult<(Contract, u64)> {
+    let contract = read_stream(input)?.contract;
+    let now = now_or_current(now);
+    if now >= contract.start_time() && now < contract.last_rate_change_time {
+        return Err(format!(
+            "--now {now} is before the last rate change at {}, earlier unlocks aren't recorded",
+            contract.last_rate_change_time
+        ));
+    }
+    Ok((contract, now))
+}
+
+fn print_status(contract: &Contract, now: u64) {
+    println!("name: {}", trim_stream_name(&contract.ix.stream_name));
+    println!("sender: {}", contract.sender);
+    println!("recipient: {}", contract.recipient);
+    println!("mint: {}", contract.mint);
+    println!("status: {}", contract.status(now));
+    println!("deposited: {}", contract.ix.net_amount_deposited);
+    println!("withdrawn: {}", contract.amount_withdrawn);
+    if contract.closed {
+        println!("closed, canceled at {}", contract.canceled_at);
+        return;
+    }
+    println!("available: {}", contract.available_to_claim(now, 100.0));
+    match contract.next_unlock(now) {
+        Some(time) => println!("next unlock: {time}"),
+        None => println!("next unlock: none"),
+    }
+    println!("{}", contract.timeline(now));
+}
+
+impl CreateArgs {
+    fn params(&self, is_pda: bool) -> CliResult<CreateParams> {
+        let stream_name =
+            pad_stream_name(&self.stream_name).ok_or("stream name is longer than 64 bytes")?;
+        Ok(CreateParams {
+            start_time: self.start_time,
+            net_amount_deposited: self.net_amount_deposited,
+            period: self.period,
+            amount_per_period: self.amount_per_period,
+            cliff: self.cliff,
+            cliff_amount: self.cliff_amount,
+            cancelable_by_sender: self.cancelable_by_sender,
+            cancelable_by_recipient: self.cancelable_by_recipient,
+            automatic_withdrawal: self.automatic_withdrawal,
+            transferable_by_sender: self.transferable_by_sender,
+            transferable_by_recipient: self.transferable_by_recipient,
+            can_topup: self.can_topup,
+            stream_name,
+            withdraw_frequency: self.withdraw_frequency,
+            ghost: 0,
+            pausable: self.pausable,
+            can_update_rate: self.can_update_rate,
+            ghost2: 0,
+            is_pda,
+            nonce: self.nonce,
+        })
+    }
+}
+
+impl CreateAccounts {
+    fn partner(&self) -> Pubkey {
+        self.partner
+            .unwrap_or_else(|| STRM_TREASURY.parse().unwrap())
+    }
+}
+
+impl UpdateFlags {
+    fn args(&self) -> UpdateArgs {
+        let mut args = UpdateArgs::new();
+        if let Some(frequency) = self.enable_automatic_withdrawal {
+            args = args.enable_automatic_withdrawal(frequency);
+        }
+        if let Some(amount_per_period) = self.amount_per_period {
+            args = args.set_rate(amount_per_period);
+        }
+        if self.disable_sender_transfer {
+            args = args.disable_sender_transfer();
+        }
+        if self.enable_recipient_transfer {
+            args = args.enable_recipient_transfer();
+        }
+        if self.disable_sender_cancel {
+            args = args.disable_sender_cancel();
+        }
+        args
+    }
+}
+
+impl StreamAccount {
+    fn contract(&self) -> CliResult<Contract> {
+        Ok(read_stream(&self.stream)?.contract)
+    }
+}
+
+impl Method {
+    fn instruction(&self) -> CliResult<Instruction> {
+        let ix = match self {
+            Method::Create {
+                accounts: a,
+                metadata,
+                params,
+            } => instructions::create(
+                a.sender,
+                *metadata,
+                a.mint,
+                a.recipient,
+                a.partner(),
+                &params.params(false)?,
+            ),
+            Method::CreateV2 {
+                accounts: a,
+                params,
+            } => instructions::create_v2(
+                a.sender,
+                a.mint,
+                a.recipient,
+                a.partner(),
+                &params.params(true)?,
+            ),
+            Method::CreateUnchecked {
+                accounts: a,
+                metadata,
+                params,
+            } => instructions::create_unchecked(
+                a.sender,
+                *metadata,
+                a.mint,
+                a.recipient,
+                a.partner(),
+                &params.params(false)?,
+            ),
+            Method::CreateUncheckedV2 {
+                accounts: a,
+                params,
+            } => instructions::create_unchecked_v2(
+                a.sender,
+                a.mint,
+                a.recipient,
+                a.partner(),
+                &params.params(true)?,
+            ),
+            Method::CreateUncheckedWithPayer {
+                accounts: a,
+                stream_payer,
+                metadata,
+                params,
+            } => instructions::create_unchecked_with_payer(
+                *stream_payer,
+                a.sender,
+                *metadata,
+                a.mint,
+                a.recipient,
+                a.partner(),
+                &params.params(false)?,
+            ),
+            Method::CreateUncheckedWithPayerV2 {
+                accounts: a,
+                stream_payer,
+                params,
+            } => instructions::create_unchecked_with_payer_v2(
+                *stream_payer,
+                a.sender,
+                a.mint,
+                a.recipient,
+                a.partner(),
+                &params.params(true)?,
+            ),
+            Method::Update {
+                sender,
+                metadata,
+                args,
+            } => {
+                let args = args.args();
+                if args == UpdateArgs::default() {
+                    return Err("invalid update: nothing to change".to_string());
+                }
+                update_instruction(*sender, *metadata, args)
+                    .map_err(|e| format!("invalid update: {e}"))?
+            }
+            Method::Withdraw {
+                stream,
+                authority,
+                amount,
+            } => instructions::withdraw(*authority, stream.metadata, &stream.contract()?, *amount),
+            Method::Cancel { stream, authority } => {
+                instructions::cancel(*authority, stream.metadata, &stream.contract()?)
+            }
+            Method::Pause { sender, metadata } => instructions::pause(*sender, *metadata),
+            Method::Unpause { sender, metadata } => instructions::unpause(*sender, *metadata),
+            Method::TransferRecipient {
+                stream,
+                authority,
+                new_recipient,
+            } => instructions::transfer_recipient(
+                *authority,
+                stream.metadata,
+                stream.contract()?.mint,
+                *new_recipient,
+            ),
+            Method::TransferSender {
+                stream,
+                sender,
+                new_sender,
+            } => instructions::transfer_sender(
+                *sender,
+                *new_sender,
+                stream.metadata,
+                stream.contract()?.mint,
+            ),
+            Method::Topup {
+                stream,
+                sender,
+                amount,
+            } => instructions::topup(*sender, stream.metadata, &stream.contract()?, *amount),
+        };
+        Ok(ix)
+    }
+}
+
+fn run(cli: Cli) -> CliResult<()> {
+    match cli.command {
+        Command::Decode(args) => {
+            let stream = read_stream(&args.stream)?;
+            println!("{}", serde_json::to_string_pretty(&stream).unwrap());
+        }
+        Command::Status(args) => {
+            let (contract, now) = stream_at(&args.stream, args.now)?;
+            print_status(&contract, now);
+        }
+        Command::Schedule(args) => {
+            let (contract, now) = stream_at(&args.stream.stream, args.stream.now)?;
+            for unlock in contract.schedule(now).take(args.limit) {
+                println!("{} {} {}", unlock.time, unlock.amount, unlock.unlocked);
+            }
+        }
+        Command::PreviewCancel(args) => {
+            let (contract, now) = stream_at(&args.stream, args.now)?;
+            let preview = contract.preview_cancel(now).map_err(|e| e.to_string())?;
+            println!("recipient: {}", preview.recipient_amount);
+            println!("sender: {}", preview.sender_amount);
+            println!("streamflow fee: {}", preview.streamflow_fee);
+            println!("partner fee: {}", preview.partner_fee);
+        }
+        Command::Derive(Derive::Metadata { mint, payer, nonce }) => {
+            let (metadata, bump) = derive_metadata(&mint, &payer, nonce, &streamflow_sdk::id());
+            let (escrow, _) = find_escrow_account(metadata.as_ref(), &streamflow_sdk::id());
+            println!("metadata: {metadata}");
+            println!("bump: {bump}");
+            println!("escrow: {escrow}");
+        }
+        Command::Derive(Derive::Escrow { metadata }) => {
+            let (escrow, _) = find_escrow_account(metadata.as_ref(), &streamflow_sdk::id());
+            println!("escrow: {escrow}");
+        }
+        Command::Build(build) => {
+            let ix = build.method.instruction()?;
+            let payer = build
+                .payer
+                .or_else(|| output::default_payer(&ix))
+                .ok_or("instruction has no signers, set --payer")?;
+            let blockhash = match (build.format, build.blockhash) {
+                // a transaction with zero blockhash can't land
+                (Format::Transaction, None) => {
+                    return Err("--format transaction requires --blockhash".to_string())
+                }
+                (_, blockhash) => blockhash.unwrap_or_default(),
+            };
+            println!("{}", output::encode(ix, build.format, &payer, blockhash));
+        }
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Encoding of built instructions
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::ValueEnum;
+use solana_hash::Hash;
+use solana_message::Message;
+use solana_transaction::Transaction;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Instruction as JSON: program id, account metas and base64 data
+    #[default]
+    Instruction,
+    /// Base64 serialized legacy message, as expected by multisig proposals
+    Message,
+    /// Base64 serialized unsigned legacy transaction
+    Transaction,
+}
+
+/// First signer of the instruction, pays for the transaction unless `--payer` is set
+pub fn default_payer(ix: &Instruction) -> Option<Pubkey> {
+    ix.accounts.iter().find(|a| a.is_signer).map(|a| a.pubkey)
+}
+
+pub fn instruction_json(ix: &Instruction) -> serde_json::Value {
+    let accounts: Vec<_> = ix
+        .accounts
+        .iter()
+        .map(|a| {
+            serde_json::json!({
+                "pubkey": a.pubkey.to_string(),
+                "is_signer": a.is_signer,
+                "is_writable": a.is_writable,
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "program_id": ix.program_id.to_string(),
+        "accounts": accounts,
+        "data": STANDARD.encode(&ix.data),
+    })
+}
+
+pub fn message(ix: Instruction, payer: &Pubkey, blockhash: Hash) -> Message {
+    Message::new_with_blockhash(&[ix], Some(payer), &blockhash)
+}
+
+pub fn encode(ix: Instruction, format: Format, payer: &Pubkey, blockhash: Hash) -> String {
+    match format {
+        Format::Instruction => serde_json::to_string_pretty(&instruction_json(&ix)).unwrap(),
+        Format::Message => STANDARD.encode(message(ix, payer, blockhash).serialize()),
+        Format::Transaction => {
+            let transaction = Transaction::new_unsigned(message(ix, payer, blockhash));
+            STANDARD.encode(bincode::serialize(&transaction).unwrap())
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use std::process::{Command, Output};
+
+use anchor_lang::prelude::*;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_message::Message;
+use streamflow_sdk::decode::{decode_instruction, StreamflowInstruction};
+
+const METADATA_V3: &str = "../streamflow-sdk/tests/fixtures/metadata_v3.bin";
+
+fn cli(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_streamflow-cli"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(args: &[&str]) -> String {
+    let output = cli(args);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn reads_file_and_base64() {
+    let base64 = STANDARD.encode(std::fs::read(METADATA_V3).unwrap());
+    let from_file: serde_json::Value =
+        serde_json::from_str(&stdout(&["decode", METADATA_V3])).unwrap();
+    let from_base64: serde_json::Value =
+        serde_json::from_str(&stdout(&["decode", &base64])).unwrap();
+    assert_eq!(from_file, from_base64);
+    assert_eq!(from_file["contract"]["version"], 3);
+
+    let status = stdout(&["status", METADATA_V3, "--now", "1700000000"]);
+    assert!(status.contains("name: Team vesting Q1"));
+    assert!(status.contains("status: scheduled"));
+    assert!(status.contains("next unlock: 1702592000"));
+
+    let error = cli(&["decode", "not a stream"]);
+    assert!(!error.status.success());
+}
+
+#[test]
+fn prints_schedule() {
+    let schedule = stdout(&[
+        "schedule",
+        METADATA_V3,
+        "--now",
+        "1700000000",
+        "--limit",
+        "3",
+    ]);
+    let unlocks: Vec<Vec<u64>> = schedule
+        .lines()
+        .map(|line| line.split(' ').map(|v| v.parse().unwrap()).collect())
+        .collect();
+    assert_eq!(unlocks.len(), 3);
+    // the cliff comes first, every row adds its amount to the total
+    assert_eq!(unlocks[0][0], 1702592000);
+    assert_eq!(unlocks[0][1], unlocks[0][2]);
+    for pair in unlocks.windows(2) {
+        assert!(pair[0][0] < pair[1][0]);
+        assert_eq!(pair[0][2] + pair[1][1], pair[1][2]);
+    }
+}
+
+#[test]
+fn builds_withdraw_message() {
+    let metadata = Pubkey::new_unique().to_string();
+    let authority = Pubkey::new_unique();
+    let encoded = stdout(&[
+        "build",
+        "withdraw",
+        "--metadata",
+        &metadata,
+        "--stream",
+        METADATA_V3,
+        "--authority",
+        &authority.to_string(),
+        "--amount",
+        "5",
+        "--format",
+        "message",
+    ]);
+    let bytes = STANDARD.decode(encoded.trim()).unwrap();
+    let message: Message = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(message.account_keys[0], authority);
+
+    let ix = &message.instructions[0];
+    let accounts: Vec<_> = ix
+        .accounts
+        .iter()
+        .map(|i| message.account_keys[*i as usize])
+        .collect();
+    match decode_instruction(&ix.data, &accounts).unwrap() {
+        StreamflowInstruction::Withdraw { args, accounts } => {
+            assert_eq!(args.amount, 5);
+            assert_eq!(accounts.metadata.to_string(), metadata);
+        }
+        other => panic!("unexpected instruction {}", other.name()),
+    }
+}
+
+#[test]
+fn derives_addresses() {
+    let mint = Pubkey::new_unique();
+    let payer = Pubkey::new_unique();
+    let (metadata, _) =
+        streamflow_sdk::state::derive_metadata(&mint, &payer, 7, &streamflow_sdk::id());
+    let output = stdout(&[
+        "derive",
+        "metadata",
+        "--mint",
+        &mint.to_string(),
+        "--payer",
+        &payer.to_string(),
+        "--nonce",
+        "7",
+    ]);
+    assert!(output.contains(&format!("metadata: {metadata}")));
+
+    let escrow = stdout(&["derive", "escrow", "--metadata", &metadata.to_string()]);
+    assert!(output.contains(escrow.trim()));
+}
+
+#[test]
+fn rejects_empty_update() {
+    let key = Pubkey::new_unique().to_string();
+    let output = cli(&["build", "update", "--sender", &key, "--metadata", &key]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid update"));
+}
+
+#[test]
+fn rejects_now_before_rate_change() {
+    const METADATA_V2: &str = "../streamflow-sdk/tests/fixtures/metadata_v2.bin";
+    for command in ["status", "preview-cancel"] {
+        let output = cli(&[command, METADATA_V2, "--now", "1702600000"]);
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(
+            stderr.contains("before the last rate change at 1705000000"),
+            "{stderr}"
+        );
+
+        stdout(&[command, METADATA_V2, "--now", "1705000000"]);
+    }
+
+    // before the recorded withdrawals nothing is available
+    const METADATA_V1: &str = "../streamflow-sdk/tests/fixtures/metadata_v1.bin";
+    let status = stdout(&["status", METADATA_V1, "--now", "1702600000"]);
+    assert!(status.contains("available: 0"));
+}
+
+#[test]
+fn transaction_requires_blockhash() {
+    let key = Pubkey::new_unique().to_string();
+    let pause = [
+        "build",
+        "--format",
+        "transaction",
+        "pause",
+        "--sender",
+        &key,
+        "--metadata",
+        &key,
+    ];
+    let output = cli(&pause);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("requires --blockhash"));
+
+    let blockhash = solana_hash::Hash::new_from_array([3; 32]);
+    let encoded = stdout(&[&pause[..], &["--blockhash", &blockhash.to_string()]].concat());
+    let transaction: solana_transaction::Transaction =
+        bincode::deserialize(&STANDARD.decode(encoded.trim()).unwrap()).unwrap();
+    assert_eq!(transaction.message.recent_blockhash, blockhash);
+}
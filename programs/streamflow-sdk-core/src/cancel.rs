@@ -0,0 +1,84 @@
+//! Off-chain preview of cancellations
+
+use core::fmt;
+
+use crate::state::Contract;
+
+/// Reasons why a cancel would be rejected
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CancelError {
+    /// Stream is already closed
+    StreamClosed,
+}
+
+impl fmt::Display for CancelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CancelError::StreamClosed => f.write_str("stream is closed"),
+        }
+    }
+}
+
+impl core::error::Error for CancelError {}
+
+/// Token transfers of a cancel and the resulting Stream
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CancelPreview {
+    /// Unlocked tokens sent to `recipient_tokens`
+    pub recipient_amount: u64,
+    /// Locked tokens returned to `sender_tokens`
+    pub sender_amount: u64,
+    /// Tokens sent to `streamflow_treasury_tokens`
+    pub streamflow_fee: u64,
+    /// Tokens sent to `partner_tokens`
+    pub partner_fee: u64,
+    /// Contract state after the cancel
+    pub contract: Contract,
+}
+
+impl Contract {
+    /// Preview a `cancel` at `now`.
+    ///
+    /// Recipient receives everything unlocked so far together with unlocked fees, the rest of
+    /// the deposit is returned to the sender. Doesn't check who is allowed to cancel.
+    pub fn preview_cancel(&self, now: u64) -> Result<CancelPreview, CancelError> {
+        if self.closed {
+            return Err(CancelError::StreamClosed);
+        }
+        let recipient_amount = self.available_to_claim(now, 100.0);
+        let sender_amount = self
+            .ix
+            .net_amount_deposited
+            .saturating_sub(self.amount_withdrawn)
+            .saturating_sub(recipient_amount);
+        let streamflow_fee = self.fee_available(
+            now,
+            self.streamflow_fee_total,
+            self.streamflow_fee_withdrawn,
+            self.streamflow_fee_percent,
+        );
+        let partner_fee = self.fee_available(
+            now,
+            self.partner_fee_total,
+            self.partner_fee_withdrawn,
+            self.partner_fee_percent,
+        );
+
+        let mut contract = self.clone();
+        contract.amount_withdrawn = contract.amount_withdrawn.saturating_add(recipient_amount);
+        contract.streamflow_fee_withdrawn = contract
+            .streamflow_fee_withdrawn
+            .saturating_add(streamflow_fee);
+        contract.partner_fee_withdrawn = contract.partner_fee_withdrawn.saturating_add(partner_fee);
+        contract.canceled_at = now;
+        contract.closed = true;
+        Ok(CancelPreview {
+            recipient_amount,
+            sender_amount,
+            streamflow_fee,
+            partner_fee,
+            contract,
+        })
+    }
+}
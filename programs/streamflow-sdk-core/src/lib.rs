@@ -0,0 +1,25 @@
+//! Stream state, PDA derivation and vesting math of the Streamflow protocol.
+//!
+//! Doesn't depend on Anchor and supports `no_std` (disable the default `std` feature), so it can be
+//! used by off-chain services and WASM builds. `streamflow-sdk` re-exports all of its modules.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(any(feature = "std", feature = "serde"))]
+extern crate alloc;
+
+pub mod cancel;
+#[cfg(feature = "std")]
+pub mod portfolio;
+#[cfg(feature = "std")]
+pub mod report;
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub mod serde_helpers;
+pub mod schedule;
+pub mod state;
+pub mod timeline;
+pub mod update;
+pub mod versioned;
+pub mod vesting;
+pub mod view;
+pub mod withdraw;
@@ -0,0 +1,222 @@
+//! Totals and future unlocks of many Streams grouped by mint
+
+use std::collections::BTreeMap;
+
+use solana_pubkey::Pubkey;
+
+use crate::state::Contract;
+
+const DAY: u64 = 86_400;
+
+/// Totals of the Streams of a single mint at a point in time
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MintTotals {
+    /// Number of Streams, including closed ones
+    pub streams: usize,
+    pub deposited: u64,
+    /// Tokens unlocked so far, for closed Streams the amount the recipient received
+    pub vested: u64,
+    pub withdrawn: u64,
+    /// Tokens recipients can withdraw now
+    pub claimable: u64,
+    /// Tokens that are still to unlock
+    pub locked: u64,
+    /// Tokens returned to senders of canceled Streams
+    pub returned: u64,
+    /// Total Streamflow fee charged on top of the deposits
+    pub streamflow_fee: u64,
+    /// Total partner fee charged on top of the deposits
+    pub partner_fee: u64,
+}
+
+impl MintTotals {
+    fn add(&mut self, contract: &Contract, now: u64) {
+        let deposited = contract.ix.net_amount_deposited;
+        let vested = if contract.closed {
+            contract.amount_withdrawn
+        } else {
+            contract.unlocked_at(now)
+        };
+        // sums over many Streams may exceed `u64`, they saturate instead of overflowing
+        self.streams += 1;
+        self.deposited = self.deposited.saturating_add(deposited);
+        self.vested = self.vested.saturating_add(vested);
+        self.withdrawn = self.withdrawn.saturating_add(contract.amount_withdrawn);
+        if contract.closed {
+            self.returned = self
+                .returned
+                .saturating_add(deposited.saturating_sub(vested));
+        } else {
+            self.claimable = self
+                .claimable
+                .saturating_add(vested.saturating_sub(contract.amount_withdrawn));
+            self.locked = self.locked.saturating_add(deposited.saturating_sub(vested));
+        }
+        self.streamflow_fee = self
+            .streamflow_fee
+            .saturating_add(contract.streamflow_fee_total);
+        self.partner_fee = self.partner_fee.saturating_add(contract.partner_fee_total);
+    }
+}
+
+/// Length of the periods future unlocks are grouped by, in UTC
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Bucket {
+    Day,
+    /// Weeks starting on Monday
+    Week,
+    /// Calendar months
+    Month,
+}
+
+impl Bucket {
+    /// Start of the period containing `time`
+    pub fn start(self, time: u64) -> u64 {
+        let days = time / DAY;
+        match self {
+            Bucket::Day => days * DAY,
+            // 1970-01-01 was a Thursday
+            Bucket::Week => days.saturating_sub((days + 3) % 7) * DAY,
+            Bucket::Month => {
+                let (year, month, _) = civil_from_days(days);
+                days_from_civil(year, month, 1) * DAY
+            }
+        }
+    }
+
+    /// Starts of the periods covering `from..to` followed by the end of the last one, boundaries
+    /// for `Contract::accrual_report`
+    pub fn boundaries(self, from: u64, to: u64) -> Vec<u64> {
+        let mut boundaries = vec![self.start(from)];
+        let mut last = boundaries[0];
+        while last < to {
+            last = self.next(last);
+            boundaries.push(last);
+        }
+        boundaries
+    }
+
+    /// Start of the period following the one starting at `start`
+    pub fn next(self, start: u64) -> u64 {
+        match self {
+            Bucket::Day => start + DAY,
+            Bucket::Week => start + 7 * DAY,
+            Bucket::Month => {
+                let (year, month, _) = civil_from_days(start / DAY);
+                let (year, month) = if month == 12 {
+                    (year + 1, 1)
+                } else {
+                    (year, month + 1)
+                };
+                days_from_civil(year, month, 1) * DAY
+            }
+        }
+    }
+}
+
+/// Tokens unlocking in the period starting at `start`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BucketedUnlock {
+    pub start: u64,
+    pub amount: u64,
+}
+
+/// Streams of a recipient or a treasury, possibly across several mints
+#[derive(Clone, Debug, Default)]
+pub struct Portfolio {
+    streams: Vec<(Pubkey, Contract)>,
+}
+
+impl Portfolio {
+    /// Create a Portfolio from `(metadata address, Contract)` pairs
+    pub fn new(streams: impl IntoIterator<Item = (Pubkey, Contract)>) -> Self {
+        Self {
+            streams: streams.into_iter().collect(),
+        }
+    }
+
+    pub fn streams(&self) -> &[(Pubkey, Contract)] {
+        &self.streams
+    }
+
+    /// Totals at `now` per mint
+    pub fn totals(&self, now: u64) -> BTreeMap<Pubkey, MintTotals> {
+        let mut totals = BTreeMap::<Pubkey, MintTotals>::new();
+        for (_, contract) in &self.streams {
+            totals.entry(contract.mint).or_default().add(contract, now);
+        }
+        totals
+    }
+
+    /// Unlocks after `now` per mint, summed over periods of `bucket` and ordered by time.
+    ///
+    /// Follows `Contract::schedule`, so unlocks of paused Streams stop at the pause start, Streams
+    /// are assumed not to be updated later and periods without unlocks are skipped. The first
+    /// period only counts unlocks after `now`.
+    pub fn unlock_schedule(
+        &self,
+        now: u64,
+        bucket: Bucket,
+    ) -> BTreeMap<Pubkey, Vec<BucketedUnlock>> {
+        let mut schedules = BTreeMap::<Pubkey, BTreeMap<u64, u64>>::new();
+        for (_, contract) in &self.streams {
+            let Some(first) = contract.next_unlock(now) else {
+                continue;
+            };
+            let buckets = schedules.entry(contract.mint).or_default();
+            let mut start = bucket.start(first);
+            let mut unlocked = contract.unlocked_at(now);
+            // the sum over a period only needs the unlocked amount at its end, so the cost
+            // doesn't depend on how many unlocks the period has
+            while unlocked < contract.ix.net_amount_deposited && start <= contract.end_time {
+                let next = bucket.next(start);
+                let unlocked_by_end = contract.unlocked_at(next - 1);
+                if unlocked_by_end > unlocked {
+                    let amount = buckets.entry(start).or_default();
+                    *amount = amount.saturating_add(unlocked_by_end - unlocked);
+                    unlocked = unlocked_by_end;
+                }
+                start = next;
+            }
+        }
+        schedules
+            .into_iter()
+            .map(|(mint, buckets)| {
+                let unlocks = buckets
+                    .into_iter()
+                    .map(|(start, amount)| BucketedUnlock { start, amount })
+                    .collect();
+                (mint, unlocks)
+            })
+            .collect()
+    }
+}
+
+/// Proleptic Gregorian date of a day counted from 1970-01-01,
+/// see <https://howardhinnant.github.io/date_algorithms.html>
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Days from 1970-01-01 to a date not earlier than it
+fn days_from_civil(year: u64, month: u64, day: u64) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let yoe = year - era * 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
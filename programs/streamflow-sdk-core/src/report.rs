@@ -0,0 +1,176 @@
+//! Tokens vested, withdrawn and fees accrued by a Stream per accounting period
+
+use std::fmt::Write;
+
+use crate::state::{calculate_fee_from_amount, Contract};
+
+/// Columns of `ReportRow::to_csv`
+pub const CSV_HEADER: &str =
+    "period_start,period_end,vested,cliff,withdrawn,streamflow_fee,partner_fee,estimated";
+
+/// Tokens sent to the recipient at `time`, by a withdrawal or a cancel.
+///
+/// The account only stores the withdrawn total, take these from `WithdrawEvent` and `CancelEvent`
+/// together with the block time of their transactions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Withdrawal {
+    pub time: u64,
+    pub amount: u64,
+}
+
+/// Amounts of a Stream in the period from `start` until `end`, excluding `end`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReportRow {
+    pub start: u64,
+    pub end: u64,
+    /// Tokens vested in the period, including the cliff amount
+    pub vested: u64,
+    /// Part of `vested` unlocked at the cliff
+    pub cliff: u64,
+    pub withdrawn: u64,
+    /// Streamflow fee accrued in the period, fees accrue together with vested tokens
+    pub streamflow_fee: u64,
+    /// Partner fee accrued in the period
+    pub partner_fee: u64,
+    /// Period overlaps history the account doesn't record exactly, see `Contract::accrual_report`
+    pub estimated: bool,
+}
+
+impl ReportRow {
+    /// Row in `CSV_HEADER` format, without a line break
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{}",
+            self.start,
+            self.end,
+            self.vested,
+            self.cliff,
+            self.withdrawn,
+            self.streamflow_fee,
+            self.partner_fee,
+            self.estimated
+        )
+    }
+}
+
+/// Header and rows as CSV
+pub fn to_csv(rows: &[ReportRow]) -> String {
+    let mut csv = String::from(CSV_HEADER);
+    csv.push('\n');
+    for row in rows {
+        let _ = writeln!(csv, "{}", row.to_csv());
+    }
+    csv
+}
+
+/// Amounts recognized by a point in time
+#[derive(Clone, Copy, Default)]
+struct Recognized {
+    streamed: u64,
+    cliff: u64,
+    streamflow_fee: u64,
+    partner_fee: u64,
+}
+
+impl Contract {
+    /// Time until which the vesting of the Stream isn't recorded exactly.
+    ///
+    /// The account only keeps the amount unlocked at the last rate change and the total duration
+    /// of past pauses, the vesting math treats them as if they happened at the start.
+    fn estimated_until(&self) -> u64 {
+        self.effective_start_time() + self.pause_cumulative
+    }
+
+    /// Amounts vested at or before `time`
+    fn recognized(&self, time: u64) -> Recognized {
+        let vesting = self.vesting();
+        // vesting and fees stop at the cancel
+        let time = if self.closed && self.canceled_at != 0 {
+            time.min(self.canceled_at)
+        } else {
+            time
+        };
+        let start = vesting.start_time();
+        if time < start {
+            return Recognized::default();
+        }
+        let deposited = self.ix.net_amount_deposited;
+        let cliff = vesting.cliff_available(time).min(deposited);
+        let estimated_until = self.estimated_until();
+        let streamed = if time < estimated_until {
+            // spread tokens unlocked before the last rate change evenly over the unrecorded history
+            (u128::from(self.funds_unlocked_at_last_rate_change) * u128::from(time - start)
+                / u128::from(estimated_until - start)) as u64
+        } else {
+            vesting.unlocked_at(time).saturating_sub(cliff)
+        };
+        let streamed = streamed.min(deposited - cliff);
+
+        let fully_unlocked = streamed + cliff == deposited;
+        let fee = |total: u64, percentage: f32| {
+            if fully_unlocked {
+                total
+            } else {
+                total.min(
+                    calculate_fee_from_amount(streamed, percentage)
+                        + calculate_fee_from_amount(cliff, percentage),
+                )
+            }
+        };
+        Recognized {
+            streamed,
+            cliff,
+            streamflow_fee: fee(self.streamflow_fee_total, self.streamflow_fee_percent),
+            partner_fee: fee(self.partner_fee_total, self.partner_fee_percent),
+        }
+    }
+
+    /// Amounts vested before `time`
+    fn recognized_before(&self, time: u64) -> Recognized {
+        match time.checked_sub(1) {
+            Some(time) => self.recognized(time),
+            None => Recognized::default(),
+        }
+    }
+
+    /// Tokens vested, withdrawn and fees accrued in each period between consecutive `boundaries`.
+    ///
+    /// `boundaries` have to be sorted, `withdrawals` are summed into the period containing their
+    /// time. Cliff amounts are recognized at the cliff, nothing vests while the Stream is paused
+    /// and vesting of a canceled Stream stops at `canceled_at`. Periods after the present assume
+    /// the Stream isn't paused, updated or canceled later.
+    ///
+    /// The account doesn't record how tokens unlocked before the last rate change or when past
+    /// pauses happened. Tokens unlocked in that time are spread evenly over it and periods
+    /// overlapping it are marked `estimated`, the totals are exact.
+    pub fn accrual_report(&self, boundaries: &[u64], withdrawals: &[Withdrawal]) -> Vec<ReportRow> {
+        let start = self.start_time();
+        let estimated_until = self.estimated_until();
+        boundaries
+            .windows(2)
+            .map(|period| {
+                let (from, to) = (period[0], period[1]);
+                let before = self.recognized_before(from);
+                let after = self.recognized_before(to);
+                let withdrawn = withdrawals
+                    .iter()
+                    .filter(|w| (from..to).contains(&w.time))
+                    .map(|w| w.amount)
+                    .sum();
+                ReportRow {
+                    start: from,
+                    end: to,
+                    vested: (after.streamed + after.cliff)
+                        .saturating_sub(before.streamed + before.cliff),
+                    cliff: after.cliff.saturating_sub(before.cliff),
+                    withdrawn,
+                    streamflow_fee: after.streamflow_fee.saturating_sub(before.streamflow_fee),
+                    partner_fee: after.partner_fee.saturating_sub(before.partner_fee),
+                    estimated: estimated_until > start && from < estimated_until && to > start,
+                }
+            })
+            .collect()
+    }
+}
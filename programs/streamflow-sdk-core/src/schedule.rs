@@ -0,0 +1,64 @@
+//! Future unlocks of a Stream
+
+use crate::state::Contract;
+
+/// Tokens unlocked at `time`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Unlock {
+    pub time: u64,
+    /// Tokens unlocked at `time`
+    pub amount: u64,
+    /// Tokens unlocked in total after `time`, including withdrawn ones
+    pub unlocked: u64,
+}
+
+/// Iterator over unlocks of a Stream, returned by `Contract::schedule`
+#[derive(Clone, Debug)]
+pub struct Schedule<'a> {
+    contract: &'a Contract,
+    time: u64,
+    unlocked: u64,
+}
+
+impl Iterator for Schedule<'_> {
+    type Item = Unlock;
+
+    fn next(&mut self) -> Option<Unlock> {
+        // the deposit may be reached before `end_time`, later periods unlock nothing
+        while self.unlocked < self.contract.ix.net_amount_deposited {
+            let time = self.contract.next_unlock(self.time)?;
+            let unlocked = self.contract.unlocked_at(time);
+            self.time = time;
+            if unlocked > self.unlocked {
+                let amount = unlocked - self.unlocked;
+                self.unlocked = unlocked;
+                return Some(Unlock {
+                    time,
+                    amount,
+                    unlocked,
+                });
+            }
+        }
+        None
+    }
+}
+
+impl Contract {
+    /// Tokens unlocked by `now` in total, including withdrawn ones
+    pub fn unlocked_at(&self, now: u64) -> u64 {
+        self.vesting().unlocked_at(now)
+    }
+
+    /// Unlocks after `now`, assuming the Stream isn't paused, updated or canceled later.
+    ///
+    /// Nothing unlocks while the Stream is paused, so the schedule of a paused Stream ends at the
+    /// pause start.
+    pub fn schedule(&self, now: u64) -> Schedule<'_> {
+        Schedule {
+            contract: self,
+            time: now,
+            unlocked: self.unlocked_at(now),
+        }
+    }
+}
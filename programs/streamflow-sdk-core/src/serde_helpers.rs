@@ -0,0 +1,41 @@
+//! Serde representations for types that don't have a human readable one on their own
+
+/// `Pubkey` as base58 string
+pub mod pubkey {
+    use alloc::string::String;
+    use core::str::FromStr;
+
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use solana_pubkey::Pubkey;
+
+    pub fn serialize<S: Serializer>(key: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(key)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Pubkey::from_str(&s).map_err(D::Error::custom)
+    }
+}
+
+/// Zero padded `stream_name` as trimmed string
+pub mod stream_name {
+    use alloc::string::String;
+
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    use crate::state::{pad_stream_name, trim_stream_name};
+
+    pub fn serialize<S: Serializer>(name: &[u8; 64], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&trim_stream_name(name))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 64], D::Error> {
+        let s = String::deserialize(deserializer)?;
+        pad_stream_name(&s).ok_or_else(|| D::Error::custom("stream name is longer than 64 bytes"))
+    }
+}
+
+pub(crate) fn ix_padding() -> [u8; crate::state::CREATE_PARAMS_PADDING] {
+    [0u8; crate::state::CREATE_PARAMS_PADDING]
+}
@@ -1,4 +1,6 @@
-use anchor_lang::prelude::*;
+use borsh::maybestd::io::{self, Read, Write};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_pubkey::Pubkey;
 
 /// Streamflow Treasury address, by default receives 0.25% of tokens deposited
 pub const STRM_TREASURY: &str = "5SEpbdjFK5FxwTvfsGMXVQTD2v4M2c5tyRTxhdsPkgDw";
@@ -6,6 +8,12 @@ pub const STRM_TREASURY: &str = "5SEpbdjFK5FxwTvfsGMXVQTD2v4M2c5tyRTxhdsPkgDw";
 pub const WITHDRAWOR_ADDRESS: &str = "wdrwhnCv4pzW8beKsbPa4S2UDZrXenjg16KJdKSpb5u";
 /// Address of Fee Oracle that stores information about fees for speficic partners
 pub const FEE_ORACLE_ADDRESS: &str = "B743wFVk2pCYhV91cn287e1xY7f1vt4gdY48hhNiuQmT";
+/// `STRM_TREASURY` as a `Pubkey`
+pub const STREAMFLOW_TREASURY: Pubkey = Pubkey::from_str_const(STRM_TREASURY);
+/// `WITHDRAWOR_ADDRESS` as a `Pubkey`
+pub const WITHDRAWOR: Pubkey = Pubkey::from_str_const(WITHDRAWOR_ADDRESS);
+/// `FEE_ORACLE_ADDRESS` as a `Pubkey`
+pub const FEE_ORACLE: Pubkey = Pubkey::from_str_const(FEE_ORACLE_ADDRESS);
 
 /// Prefix used to derive Escrow account address
 pub const ESCROW_SEED_PREFIX: &[u8] = b"strm";
@@ -20,6 +28,23 @@ pub const METADATA_LEN: usize = 1104;
 pub const STREAMFLOW_PROGRAM_ID: &str = "strmRqUCoQUgGUan5YhzUZa6KqdzwX5L6FpUxfmKg5m";
 pub const STREAMFLOW_DEVNET_PROGRAM_ID: &str = "HqDGZjaVRXJ9MGRQEw7qDc2rAr6iH1n1kAQdCZaCMfMZ";
 
+/// Cluster the Streamflow program is deployed to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Cluster {
+    Mainnet,
+    Devnet,
+}
+
+impl Cluster {
+    pub fn program_id(self) -> Pubkey {
+        match self {
+            Cluster::Mainnet => STREAMFLOW_PROGRAM_ID.parse().unwrap(),
+            Cluster::Devnet => STREAMFLOW_DEVNET_PROGRAM_ID.parse().unwrap(),
+        }
+    }
+}
+
 pub fn find_escrow_account(seed: &[u8], pid: &Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(&[ESCROW_SEED_PREFIX, seed], pid)
 }
@@ -50,8 +75,23 @@ pub fn calculate_fee_from_amount(amount: u64, percentage: f32) -> u64 {
     (amount as u128 * factor / precision_factor as u128) as u64
 }
 
+/// `name` zero padded to `CreateParams::stream_name`, `None` if it's longer than 64 bytes
+pub fn pad_stream_name(name: &str) -> Option<[u8; 64]> {
+    let mut padded = [0; 64];
+    padded.get_mut(..name.len())?.copy_from_slice(name.as_bytes());
+    Some(padded)
+}
+
+/// `CreateParams::stream_name` without the zero padding, invalid UTF-8 is replaced
+#[cfg(any(feature = "std", feature = "serde"))]
+pub fn trim_stream_name(name: &[u8; 64]) -> alloc::string::String {
+    let len = name.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    alloc::string::String::from_utf8_lossy(&name[..len]).into_owned()
+}
+
 /// The struct containing parameters for initializing a stream
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct CreateParams {
     /// Timestamp when the tokens start vesting
@@ -79,6 +119,7 @@ pub struct CreateParams {
     /// Whether topup is enabled
     pub can_topup: bool,
     /// The name of this stream
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::stream_name"))]
     pub stream_name: [u8; 64],
     /// Withdraw frequency
     pub withdraw_frequency: u64,
@@ -100,13 +141,20 @@ pub struct CreateParams {
 ///
 /// May be read like so
 ///
-/// ```rust
-/// let stream_metadata: Contract = match try_from_slice_unchecked(&stream_data) {
+/// ```
+/// # use streamflow_sdk_core::state::Contract;
+/// use borsh::BorshDeserialize;
+///
+/// # fn read(stream_data: &[u8]) -> Result<Contract, &'static str> {
+/// let stream_metadata = match Contract::deserialize(&mut &stream_data[..]) {
 ///     Ok(v) => v,
-///     Err(_) => return err!(ErrorCode::InvalidStreamMetadata),
+///     Err(_) => return Err("invalid stream metadata"),
 /// };
+/// # Ok(stream_metadata)
+/// # }
 /// ```
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct Contract {
     /// Magic bytes
@@ -126,20 +174,28 @@ pub struct Contract {
     /// Timestamp of the last withdrawal
     pub last_withdrawn_at: u64,
     /// Pubkey of the stream initializer
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
     pub sender: Pubkey,
     /// Pubkey of the stream initializer's token account
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
     pub sender_tokens: Pubkey,
     /// Pubkey of the stream recipient
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
     pub recipient: Pubkey,
     /// Pubkey of the stream recipient's token account
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
     pub recipient_tokens: Pubkey,
     /// Pubkey of the token mint
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
     pub mint: Pubkey,
     /// Escrow account holding the locked tokens for recipient
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
     pub escrow_tokens: Pubkey,
     /// Streamflow treasury authority
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
     pub streamflow_treasury: Pubkey,
     /// Escrow account holding the locked tokens for Streamflow (fee account)
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
     pub streamflow_treasury_tokens: Pubkey,
     /// The total fee amount for streamflow
     pub streamflow_fee_total: u64,
@@ -148,8 +204,10 @@ pub struct Contract {
     /// Fee percentage for Streamflow
     pub streamflow_fee_percent: f32,
     /// Streamflow partner authority
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
     pub partner: Pubkey,
     /// Escrow account holding the locked tokens for Streamflow partner (fee account)
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
     pub partner_tokens: Pubkey,
     /// The total fee amount for the partner
     pub partner_fee_total: u64,
@@ -160,6 +218,10 @@ pub struct Contract {
     /// The stream instruction
     pub ix: CreateParams,
     /// Padding for `ix: CreateParams` to allow for future upgrades.
+    #[cfg_attr(
+        feature = "serde",
+        serde(skip, default = "crate::serde_helpers::ix_padding")
+    )]
     pub ix_padding: [u8; CREATE_PARAMS_PADDING],
     /// Whether Stream is closed
     pub closed: bool,
@@ -182,8 +244,10 @@ pub struct Contract {
     pub auto_claim_fee_claimed: bool,
     /// Pubkey of the old (drained) metadata account, used for escrow PDA derivation.
     /// Zero (default) means this IS the original metadata -- escrow derives from self.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
     pub old_metadata: Pubkey,
     /// Wallet that paid for the Contract creation, may be used for PDA derivation
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
     pub payer: Pubkey,
     /// Bump used for the Contract Metadata if it's a PDA
     pub bump: u8,
@@ -191,37 +255,20 @@ pub struct Contract {
 
 impl Contract {
     pub fn start_time(&self) -> u64 {
-        if self.ix.cliff > 0 {
-            self.ix.cliff
-        } else {
-            self.ix.start_time
-        }
+        self.vesting().start_time()
     }
 
     pub fn effective_start_time(&self) -> u64 {
-        std::cmp::max(self.last_rate_change_time, self.start_time())
+        self.vesting().effective_start_time()
     }
 
     pub fn pause_time(&self, now: u64) -> u64 {
-        if self.current_pause_start > 0 {
-            return self.pause_cumulative + now - self.current_pause_start;
-        }
-        self.pause_cumulative
+        self.vesting().pause_time(now)
     }
 
     /// amount available that is vested (excluding cliff)
     pub fn vested_available(&self, now: u64) -> u64 {
-        let start = self.start_time();
-        // if pause started before start/cliff and is still active, no unlocks
-        if self.current_pause_start < start && self.current_pause_start != 0 {
-            return 0;
-        }
-        // available from streaming based on current rate
-        let effective_stream_duration = now - self.effective_start_time() - self.pause_time(now);
-        let effective_periods_passed = effective_stream_duration / self.ix.period;
-        let effective_amount_available = effective_periods_passed * self.ix.amount_per_period;
-
-        effective_amount_available + self.funds_unlocked_at_last_rate_change
+        self.vesting().vested_available(now)
     }
 
     pub fn available_to_claim(&self, now: u64, fee_percentage: f32) -> u64 {
@@ -239,16 +286,141 @@ impl Contract {
             calculate_fee_from_amount(self.vested_available(now), fee_percentage);
         let cliff_available = calculate_fee_from_amount(self.cliff_available(now), fee_percentage);
         let sum_available = vested_available + cliff_available;
-        sum_available - self.amount_withdrawn
+        // `now` may precede withdrawals already counted in `amount_withdrawn`
+        sum_available.saturating_sub(self.amount_withdrawn)
     }
 
     pub fn cliff_available(&self, now: u64) -> u64 {
-        if self.current_pause_start < self.ix.cliff && self.current_pause_start != 0 {
-            return 0;
+        self.vesting().cliff_available(now)
+    }
+}
+
+/// Field of an account stored in the layout of the protocol
+///
+/// `Pubkey` implements borsh only together with `std`, so the layout is written out by hand to
+/// stay `no_std`. Keys are stored as 32 raw bytes, exactly as borsh would store them.
+trait LayoutField: Sized {
+    fn write<W: Write>(&self, writer: &mut W) -> io::Result<()>;
+    fn read<R: Read>(reader: &mut R) -> io::Result<Self>;
+}
+
+macro_rules! borsh_field {
+    ($($ty:ty),*) => {
+        $(impl LayoutField for $ty {
+            fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                BorshSerialize::serialize(self, writer)
+            }
+
+            fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+                BorshDeserialize::deserialize_reader(reader)
+            }
+        })*
+    };
+}
+
+borsh_field!(bool, u8, u32, u64, f32, CreateParams);
+
+impl<const N: usize> LayoutField for [u8; N] {
+    fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(self)
+    }
+
+    fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut bytes = [0; N];
+        reader.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+impl LayoutField for Pubkey {
+    fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(self.as_ref())
+    }
+
+    fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        <[u8; 32]>::read(reader).map(Pubkey::new_from_array)
+    }
+}
+
+/// Implement borsh for a struct, fields have to be listed in declaration order
+macro_rules! layout {
+    ($name:ident { $($field:ident),* $(,)? }) => {
+        impl BorshSerialize for $name {
+            fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                $(self.$field.write(writer)?;)*
+                Ok(())
+            }
         }
-        if now < self.ix.cliff {
-            return 0;
+
+        impl BorshDeserialize for $name {
+            fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+                Ok(Self {
+                    $($field: LayoutField::read(reader)?,)*
+                })
+            }
         }
-        self.ix.cliff_amount
-    }
+    };
 }
+
+layout!(CreateParams {
+    start_time,
+    net_amount_deposited,
+    period,
+    amount_per_period,
+    cliff,
+    cliff_amount,
+    cancelable_by_sender,
+    cancelable_by_recipient,
+    automatic_withdrawal,
+    transferable_by_sender,
+    transferable_by_recipient,
+    can_topup,
+    stream_name,
+    withdraw_frequency,
+    ghost,
+    pausable,
+    can_update_rate,
+    ghost2,
+    is_pda,
+    nonce,
+});
+
+layout!(Contract {
+    magic,
+    version,
+    created_at,
+    amount_withdrawn,
+    canceled_at,
+    end_time,
+    last_withdrawn_at,
+    sender,
+    sender_tokens,
+    recipient,
+    recipient_tokens,
+    mint,
+    escrow_tokens,
+    streamflow_treasury,
+    streamflow_treasury_tokens,
+    streamflow_fee_total,
+    streamflow_fee_withdrawn,
+    streamflow_fee_percent,
+    partner,
+    partner_tokens,
+    partner_fee_total,
+    partner_fee_withdrawn,
+    partner_fee_percent,
+    ix,
+    ix_padding,
+    closed,
+    current_pause_start,
+    pause_cumulative,
+    last_rate_change_time,
+    funds_unlocked_at_last_rate_change,
+    creation_fee,
+    creation_fee_claimed,
+    auto_claim_fee,
+    auto_claim_fee_claimed,
+    old_metadata,
+    payer,
+    bump,
+});
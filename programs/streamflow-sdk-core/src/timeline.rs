@@ -0,0 +1,181 @@
+//! Explanation of how pauses shift the unlock schedule of a Stream
+
+use core::fmt;
+
+use crate::state::Contract;
+
+/// Rule the vesting math applies to a Stream because of its pause state
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PauseRule {
+    /// Stream isn't paused, past pauses only delay unlocks
+    NotPaused,
+    /// Paused before `start_time`, nothing unlocks until the Stream is unpaused
+    PreStartPause,
+    /// Paused after `start_time` but before the cliff, neither the cliff amount nor the streamed
+    /// amount unlock until the Stream is unpaused
+    InCliffPause,
+    /// Paused after unlocks began, amount unlocked at the pause start stays available
+    MidStreamPause,
+}
+
+impl fmt::Display for PauseRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            PauseRule::NotPaused => "not paused, past pauses delay unlocks by their duration",
+            PauseRule::PreStartPause => {
+                "paused before the start, nothing unlocks until unpaused and the whole pause \
+                 delays unlocks"
+            }
+            PauseRule::InCliffPause => {
+                "paused before the cliff, neither the cliff nor streamed amount unlock until \
+                 unpaused and the whole pause delays unlocks"
+            }
+            PauseRule::MidStreamPause => {
+                "paused mid-stream, unlocks stop until unpaused, already unlocked amount stays \
+                 available"
+            }
+        };
+        f.write_str(msg)
+    }
+}
+
+/// Lifecycle stage of a Stream at a point in time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StreamStatus {
+    /// Nothing unlocked yet, unlocks begin at the cliff or `start_time`
+    Scheduled,
+    /// Tokens are unlocking
+    Streaming,
+    /// Unlocks are stopped until the Stream is unpaused
+    Paused,
+    /// Everything is unlocked, some tokens may still be in the escrow
+    Completed,
+    /// Stream was canceled or fully withdrawn and closed
+    Closed,
+}
+
+impl fmt::Display for StreamStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            StreamStatus::Scheduled => "scheduled",
+            StreamStatus::Streaming => "streaming",
+            StreamStatus::Paused => "paused",
+            StreamStatus::Completed => "completed",
+            StreamStatus::Closed => "closed",
+        };
+        f.write_str(msg)
+    }
+}
+
+/// Effective schedule of a Stream at a point in time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StreamTimeline {
+    pub now: u64,
+    /// Time unlocks begin, the cliff if set, otherwise `start_time`
+    pub start_time: u64,
+    /// Time the current rate is counted from, later than `start_time` after a rate update
+    pub effective_start_time: u64,
+    /// Start of the active pause
+    pub current_pause_start: Option<u64>,
+    /// Seconds paused by pauses that already ended
+    pub past_paused_seconds: u64,
+    /// Seconds paused at `now`, including the active pause
+    pub paused_seconds: u64,
+    /// Time the Stream fully unlocks if it's unpaused at `now`
+    pub end_time: u64,
+    pub rule: PauseRule,
+}
+
+impl StreamTimeline {
+    pub fn is_paused(&self) -> bool {
+        self.current_pause_start.is_some()
+    }
+
+    /// Seconds the active pause lasts at `now`
+    pub fn current_pause_seconds(&self) -> u64 {
+        self.paused_seconds - self.past_paused_seconds
+    }
+}
+
+impl fmt::Display for StreamTimeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "unlocks start at {}", self.start_time)?;
+        if self.effective_start_time != self.start_time {
+            writeln!(
+                f,
+                "current rate applies since {}",
+                self.effective_start_time
+            )?;
+        }
+        if let Some(pause_start) = self.current_pause_start {
+            writeln!(
+                f,
+                "paused since {pause_start} for {} seconds",
+                self.current_pause_seconds()
+            )?;
+        }
+        writeln!(
+            f,
+            "paused for {} seconds in total, {} of them before the active pause",
+            self.paused_seconds, self.past_paused_seconds
+        )?;
+        writeln!(f, "ends at {}", self.end_time)?;
+        write!(f, "{}", self.rule)
+    }
+}
+
+impl Contract {
+    /// Explain how pauses affect unlocks of the Stream at `now`.
+    ///
+    /// Follows the same rules as `vested_available` and `cliff_available`.
+    pub fn timeline(&self, now: u64) -> StreamTimeline {
+        let paused = self.current_pause_start != 0;
+        let current_pause = if paused {
+            now.saturating_sub(self.current_pause_start)
+        } else {
+            0
+        };
+        let rule = if !paused {
+            PauseRule::NotPaused
+        } else if self.current_pause_start < self.ix.start_time {
+            PauseRule::PreStartPause
+        } else if self.current_pause_start < self.start_time() {
+            PauseRule::InCliffPause
+        } else {
+            PauseRule::MidStreamPause
+        };
+        StreamTimeline {
+            now,
+            start_time: self.start_time(),
+            effective_start_time: self.effective_start_time(),
+            current_pause_start: paused.then_some(self.current_pause_start),
+            past_paused_seconds: self.pause_cumulative,
+            paused_seconds: self.pause_cumulative + current_pause,
+            end_time: self.end_time.saturating_add(current_pause),
+            rule,
+        }
+    }
+
+    pub fn status(&self, now: u64) -> StreamStatus {
+        if self.closed {
+            StreamStatus::Closed
+        } else if self.current_pause_start != 0 {
+            StreamStatus::Paused
+        } else if now < self.start_time() {
+            StreamStatus::Scheduled
+        } else if now >= self.end_time {
+            StreamStatus::Completed
+        } else {
+            StreamStatus::Streaming
+        }
+    }
+
+    /// Time of the next unlock after `now`, `None` if nothing unlocks until an unpause or the
+    /// Stream is fully unlocked
+    pub fn next_unlock(&self, now: u64) -> Option<u64> {
+        self.vesting().next_unlock(now)
+    }
+}
@@ -0,0 +1,248 @@
+use core::fmt;
+
+use crate::state::Contract;
+
+/// Arguments of the `update` instruction
+///
+/// Every field maps to the argument of the same name, `None` leaves the value untouched.
+/// Prefer building it with the intention-revealing methods instead of setting fields directly:
+///
+/// ```
+/// # use streamflow_sdk_core::update::UpdateArgs;
+/// let args = UpdateArgs::new()
+///     .enable_automatic_withdrawal(3600)
+///     .disable_sender_cancel();
+/// # assert!(args.validate().is_ok());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UpdateArgs {
+    /// Enable automatic withdrawals, can't be disabled once enabled
+    pub enable_automatic_withdrawal: Option<bool>,
+    /// Withdrawal frequency, only used together with `enable_automatic_withdrawal`
+    pub withdraw_frequency: Option<u64>,
+    /// New Unlock Amount, requires `can_update_rate` on the Stream
+    pub amount_per_period: Option<u64>,
+    /// Transfer by Sender, can only be disabled
+    pub transferable_by_sender: Option<bool>,
+    /// Transfer by Recipient, can only be enabled
+    pub transferable_by_recipient: Option<bool>,
+    /// Cancel by Sender, can only be disabled
+    pub cancelable_by_sender: Option<bool>,
+}
+
+/// Reasons why an `update` would be rejected by the protocol
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UpdateError {
+    /// Stream is already closed
+    StreamClosed,
+    /// Automatic withdrawal is enabled and can't be turned off
+    AutomaticWithdrawalCannotBeDisabled,
+    /// `withdraw_frequency` was passed without enabling automatic withdrawal
+    WithdrawFrequencyWithoutAutomaticWithdrawal,
+    /// Stream has been created without `can_update_rate`
+    RateUpdateNotAllowed,
+    /// Stream is paused, rate changes are only previewed for unpaused Streams
+    RateUpdateWhilePaused,
+    /// `amount_per_period` can't be 0
+    ZeroAmountPerPeriod,
+    /// `withdraw_frequency` can't be 0
+    ZeroWithdrawFrequency,
+    /// Transfer by Sender can only be disabled
+    SenderTransferCannotBeEnabled,
+    /// Transfer by Recipient can only be enabled
+    RecipientTransferCannotBeDisabled,
+    /// Cancel by Sender can only be disabled
+    SenderCancelCannotBeEnabled,
+}
+
+impl fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            UpdateError::StreamClosed => "stream is closed",
+            UpdateError::AutomaticWithdrawalCannotBeDisabled => {
+                "automatic withdrawal can't be disabled"
+            }
+            UpdateError::WithdrawFrequencyWithoutAutomaticWithdrawal => {
+                "withdraw frequency requires enabling automatic withdrawal"
+            }
+            UpdateError::RateUpdateNotAllowed => "stream rate can't be updated",
+            UpdateError::RateUpdateWhilePaused => "rate of a paused stream can't be previewed",
+            UpdateError::ZeroAmountPerPeriod => "amount per period can't be 0",
+            UpdateError::ZeroWithdrawFrequency => "withdraw frequency can't be 0",
+            UpdateError::SenderTransferCannotBeEnabled => "transfer by sender can only be disabled",
+            UpdateError::RecipientTransferCannotBeDisabled => {
+                "transfer by recipient can only be enabled"
+            }
+            UpdateError::SenderCancelCannotBeEnabled => "cancel by sender can only be disabled",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl core::error::Error for UpdateError {}
+
+impl UpdateArgs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable automatic withdrawals every `withdraw_frequency` seconds
+    pub fn enable_automatic_withdrawal(mut self, withdraw_frequency: u64) -> Self {
+        self.enable_automatic_withdrawal = Some(true);
+        self.withdraw_frequency = Some(withdraw_frequency);
+        self
+    }
+
+    /// Set new Unlock Amount released every `period`
+    pub fn set_rate(mut self, amount_per_period: u64) -> Self {
+        self.amount_per_period = Some(amount_per_period);
+        self
+    }
+
+    /// Forbid Sender from transferring the Stream
+    pub fn disable_sender_transfer(mut self) -> Self {
+        self.transferable_by_sender = Some(false);
+        self
+    }
+
+    /// Allow Recipient to transfer the Stream
+    pub fn enable_recipient_transfer(mut self) -> Self {
+        self.transferable_by_recipient = Some(true);
+        self
+    }
+
+    /// Forbid Sender from cancelling the Stream
+    pub fn disable_sender_cancel(mut self) -> Self {
+        self.cancelable_by_sender = Some(false);
+        self
+    }
+
+    /// Check arguments for combinations the protocol never accepts, regardless of the Stream state.
+    ///
+    /// Flags pointing in the direction they can't be changed to are only rejected by
+    /// `Contract::preview_update`, they're no-ops if the Stream already has that value.
+    pub fn validate(&self) -> Result<(), UpdateError> {
+        if self.withdraw_frequency.is_some() && self.enable_automatic_withdrawal != Some(true) {
+            return Err(UpdateError::WithdrawFrequencyWithoutAutomaticWithdrawal);
+        }
+        if self.withdraw_frequency == Some(0) {
+            return Err(UpdateError::ZeroWithdrawFrequency);
+        }
+        if self.amount_per_period == Some(0) {
+            return Err(UpdateError::ZeroAmountPerPeriod);
+        }
+        Ok(())
+    }
+
+    /// Check that the flags don't change values of `contract` in a direction the protocol forbids
+    fn validate_directions(&self, contract: &Contract) -> Result<(), UpdateError> {
+        let ix = &contract.ix;
+        if self.enable_automatic_withdrawal == Some(false) && ix.automatic_withdrawal {
+            return Err(UpdateError::AutomaticWithdrawalCannotBeDisabled);
+        }
+        if self.transferable_by_sender == Some(true) && !ix.transferable_by_sender {
+            return Err(UpdateError::SenderTransferCannotBeEnabled);
+        }
+        if self.transferable_by_recipient == Some(false) && ix.transferable_by_recipient {
+            return Err(UpdateError::RecipientTransferCannotBeDisabled);
+        }
+        if self.cancelable_by_sender == Some(true) && !ix.cancelable_by_sender {
+            return Err(UpdateError::SenderCancelCannotBeEnabled);
+        }
+        Ok(())
+    }
+}
+
+/// Result of `Contract::preview_update`
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UpdatePreview {
+    /// Contract state after the update
+    pub contract: Contract,
+    /// Timestamp at which the Stream will be fully unlocked after the update
+    pub end_time: u64,
+}
+
+impl Contract {
+    /// Validate `args` against the rules of the `update` instruction and compute the resulting
+    /// Contract state as if the update was executed at `now`.
+    ///
+    /// When the rate changes, tokens unlocked so far are stored in
+    /// `funds_unlocked_at_last_rate_change` and streaming continues from `now` with the new rate.
+    /// Rate changes of paused Streams are rejected.
+    pub fn preview_update(
+        &self,
+        args: impl Into<UpdateArgs>,
+        now: u64,
+    ) -> Result<UpdatePreview, UpdateError> {
+        let args = args.into();
+        args.validate()?;
+        if self.closed {
+            return Err(UpdateError::StreamClosed);
+        }
+        args.validate_directions(self)?;
+        if args.amount_per_period.is_some() && !self.ix.can_update_rate {
+            return Err(UpdateError::RateUpdateNotAllowed);
+        }
+        if args.amount_per_period.is_some() && self.current_pause_start != 0 {
+            return Err(UpdateError::RateUpdateWhilePaused);
+        }
+
+        let mut contract = self.clone();
+        if args.enable_automatic_withdrawal == Some(true) {
+            contract.ix.automatic_withdrawal = true;
+            if let Some(withdraw_frequency) = args.withdraw_frequency {
+                contract.ix.withdraw_frequency = withdraw_frequency;
+            }
+        }
+        if let Some(transferable_by_sender) = args.transferable_by_sender {
+            contract.ix.transferable_by_sender = transferable_by_sender;
+        }
+        if let Some(transferable_by_recipient) = args.transferable_by_recipient {
+            contract.ix.transferable_by_recipient = transferable_by_recipient;
+        }
+        if let Some(cancelable_by_sender) = args.cancelable_by_sender {
+            contract.ix.cancelable_by_sender = cancelable_by_sender;
+        }
+        if let Some(amount_per_period) = args.amount_per_period {
+            contract.apply_rate_change(amount_per_period, now);
+        }
+
+        let end_time = contract.end_time;
+        Ok(UpdatePreview { contract, end_time })
+    }
+
+    fn apply_rate_change(&mut self, amount_per_period: u64, now: u64) {
+        let effective_start = self.effective_start_time();
+        if now > effective_start {
+            let streamed = now
+                .saturating_sub(effective_start)
+                .saturating_sub(self.pause_time(now));
+            // past `end_time` whole periods overshoot the deposit, only the deposit unlocks
+            let streamable = self
+                .ix
+                .net_amount_deposited
+                .saturating_sub(self.ix.cliff_amount);
+            self.funds_unlocked_at_last_rate_change = self
+                .funds_unlocked_at_last_rate_change
+                .saturating_add(
+                    (streamed / self.ix.period).saturating_mul(self.ix.amount_per_period),
+                )
+                .min(streamable);
+            // pauses before the change are already accounted for in the unlocked funds
+            self.pause_cumulative = 0;
+        }
+        self.last_rate_change_time = now;
+        self.ix.amount_per_period = amount_per_period;
+
+        let streamable = self
+            .ix
+            .net_amount_deposited
+            .saturating_sub(self.ix.cliff_amount)
+            .saturating_sub(self.funds_unlocked_at_last_rate_change);
+        let periods_left = streamable.div_ceil(amount_per_period);
+        self.end_time =
+            self.effective_start_time() + self.pause_cumulative + periods_left * self.ix.period;
+    }
+}
@@ -0,0 +1,55 @@
+use borsh::maybestd::io;
+use borsh::BorshDeserialize;
+use solana_pubkey::Pubkey;
+
+use crate::state::Contract;
+
+/// `Contract` decoded from metadata accounts of any generation
+///
+/// Fields introduced by later generations of the protocol are zero padding in older Streams.
+/// Accessors return `None` for fields that are unset, so zero padding is never mistaken for a
+/// real value. They look at the fields themselves, not at `Contract::version`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VersionedContract {
+    pub contract: Contract,
+}
+
+impl VersionedContract {
+    /// Decode metadata account data
+    pub fn decode(data: &[u8]) -> io::Result<Self> {
+        let contract = Contract::deserialize(&mut &data[..])?;
+        Ok(Self::from(contract))
+    }
+
+    /// Nonce used to derive the metadata PDA, only set for PDA Streams
+    pub fn nonce(&self) -> Option<u32> {
+        self.contract.ix.is_pda.then_some(self.contract.ix.nonce)
+    }
+
+    /// Bump of the metadata PDA, only set for PDA Streams
+    pub fn bump(&self) -> Option<u8> {
+        self.contract.ix.is_pda.then_some(self.contract.bump)
+    }
+
+    /// Drained metadata account the escrow is derived from, `None` if the escrow is derived
+    /// from the metadata account itself
+    pub fn old_metadata(&self) -> Option<Pubkey> {
+        self.pda_key(self.contract.old_metadata)
+    }
+
+    /// Wallet that paid for the Stream creation
+    pub fn payer(&self) -> Option<Pubkey> {
+        self.pda_key(self.contract.payer)
+    }
+
+    fn pda_key(&self, key: Pubkey) -> Option<Pubkey> {
+        (key != Pubkey::default()).then_some(key)
+    }
+}
+
+impl From<Contract> for VersionedContract {
+    fn from(contract: Contract) -> Self {
+        Self { contract }
+    }
+}
@@ -0,0 +1,225 @@
+//! Vesting math over the fields of a Stream it depends on
+//!
+//! `Contract` delegates its vesting methods to `Vesting`, `view::ContractView` reads the same fields
+//! straight from account data so on-chain programs don't have to deserialize the whole account.
+
+use crate::state::Contract;
+
+/// Fields of a Stream the vesting math depends on, see `Contract::vesting`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Vesting {
+    pub(crate) start_time: u64,
+    pub(crate) net_amount_deposited: u64,
+    pub(crate) period: u64,
+    pub(crate) amount_per_period: u64,
+    pub(crate) cliff: u64,
+    pub(crate) cliff_amount: u64,
+    pub(crate) end_time: u64,
+    pub(crate) closed: bool,
+    pub(crate) canceled_at: u64,
+    pub(crate) current_pause_start: u64,
+    pub(crate) pause_cumulative: u64,
+    pub(crate) last_rate_change_time: u64,
+    pub(crate) funds_unlocked_at_last_rate_change: u64,
+}
+
+impl Contract {
+    pub fn vesting(&self) -> Vesting {
+        Vesting {
+            start_time: self.ix.start_time,
+            net_amount_deposited: self.ix.net_amount_deposited,
+            period: self.ix.period,
+            amount_per_period: self.ix.amount_per_period,
+            cliff: self.ix.cliff,
+            cliff_amount: self.ix.cliff_amount,
+            end_time: self.end_time,
+            closed: self.closed,
+            canceled_at: self.canceled_at,
+            current_pause_start: self.current_pause_start,
+            pause_cumulative: self.pause_cumulative,
+            last_rate_change_time: self.last_rate_change_time,
+            funds_unlocked_at_last_rate_change: self.funds_unlocked_at_last_rate_change,
+        }
+    }
+
+    /// See `Vesting::unvested_at`
+    pub fn unvested_at(&self, ts: u64) -> u64 {
+        self.vesting().unvested_at(ts)
+    }
+
+    /// See `Vesting::locked_at`
+    pub fn locked_at(&self, ts: u64) -> u64 {
+        self.vesting().locked_at(ts)
+    }
+
+    /// See `Vesting::lock_weight`
+    pub fn lock_weight(&self, ts: u64) -> u128 {
+        self.vesting().lock_weight(ts)
+    }
+}
+
+impl Vesting {
+    pub fn start_time(&self) -> u64 {
+        if self.cliff > 0 {
+            self.cliff
+        } else {
+            self.start_time
+        }
+    }
+
+    pub fn effective_start_time(&self) -> u64 {
+        core::cmp::max(self.last_rate_change_time, self.start_time())
+    }
+
+    /// Seconds paused by `now`, the active pause doesn't count before it started
+    pub fn pause_time(&self, now: u64) -> u64 {
+        if self.current_pause_start > 0 {
+            return self.pause_cumulative + now.saturating_sub(self.current_pause_start);
+        }
+        self.pause_cumulative
+    }
+
+    /// amount available that is vested (excluding cliff)
+    pub fn vested_available(&self, now: u64) -> u64 {
+        let start = self.start_time();
+        // if pause started before start/cliff and is still active, no unlocks
+        if self.current_pause_start < start && self.current_pause_start != 0 {
+            return 0;
+        }
+        // available from streaming based on current rate, before the effective start only the
+        // funds unlocked at the last rate change
+        let effective_stream_duration = now
+            .saturating_sub(self.effective_start_time())
+            .saturating_sub(self.pause_time(now));
+        let effective_periods_passed = effective_stream_duration / self.period;
+        let effective_amount_available = effective_periods_passed * self.amount_per_period;
+
+        effective_amount_available + self.funds_unlocked_at_last_rate_change
+    }
+
+    pub fn cliff_available(&self, now: u64) -> u64 {
+        if self.current_pause_start < self.cliff && self.current_pause_start != 0 {
+            return 0;
+        }
+        if now < self.cliff {
+            return 0;
+        }
+        self.cliff_amount
+    }
+
+    /// Tokens unlocked by `now` in total, including withdrawn ones
+    pub fn unlocked_at(&self, now: u64) -> u64 {
+        let deposited = self.net_amount_deposited;
+        if now < self.start_time() || deposited == 0 {
+            return 0;
+        }
+        if now >= self.end_time && self.current_pause_start == 0 {
+            return deposited;
+        }
+        (self.vested_available(now) + self.cliff_available(now)).min(deposited)
+    }
+
+    /// Time of the next unlock after `now`, `None` if nothing unlocks until an unpause or the
+    /// Stream is fully unlocked.
+    ///
+    /// Unlocks of a paused Stream stop at the pause start, so only earlier ones are returned.
+    pub fn next_unlock(&self, now: u64) -> Option<u64> {
+        if self.closed || now >= self.end_time {
+            return None;
+        }
+        let paused = self.current_pause_start != 0;
+        if paused
+            && (now >= self.current_pause_start || self.current_pause_start < self.start_time())
+        {
+            return None;
+        }
+        let start = self.start_time();
+        let next = if now < start {
+            start
+        } else if self.period == 0 || self.amount_per_period == 0 {
+            return None;
+        } else {
+            // streaming at the current rate begins a period after the effective start, delayed
+            // by past pauses
+            let streaming_start = self.effective_start_time() + self.pause_cumulative;
+            if now < streaming_start {
+                streaming_start + self.period
+            } else {
+                now + self.period - (now - streaming_start) % self.period
+            }
+        };
+        let next = next.min(self.end_time);
+        if paused && next > self.current_pause_start {
+            return None;
+        }
+        Some(next)
+    }
+
+    /// Deposited tokens that aren't vested by `ts`, regardless of how many were withdrawn.
+    ///
+    /// Vesting of a canceled Stream stops at `canceled_at`.
+    pub fn unvested_at(&self, ts: u64) -> u64 {
+        let ts = if self.closed && self.canceled_at != 0 {
+            ts.min(self.canceled_at)
+        } else {
+            ts
+        };
+        self.net_amount_deposited - self.unlocked_at(ts)
+    }
+
+    /// Tokens held in the escrow at `ts` that the recipient can't withdraw yet, 0 once the Stream
+    /// is closed
+    pub fn locked_at(&self, ts: u64) -> u64 {
+        if self.closed {
+            return 0;
+        }
+        self.unvested_at(ts)
+    }
+
+    /// Locked tokens weighted by how long they stay locked: the sum of `amount * (time - ts)`
+    /// over unlocks after `ts`, in token-seconds.
+    ///
+    /// Assumes the Stream isn't paused, updated or canceled later. The end of a paused Stream
+    /// isn't known, so its weight is 0 until it's unpaused, like the weight of a closed Stream.
+    /// Doesn't visit unlocks one by one, so the cost doesn't grow with the number of periods.
+    pub fn lock_weight(&self, ts: u64) -> u128 {
+        if self.closed || self.current_pause_start != 0 {
+            return 0;
+        }
+        let deposited = self.net_amount_deposited;
+        let step = self.amount_per_period;
+        let mut weight = 0u128;
+        let mut time = ts;
+        let mut unlocked = self.unlocked_at(ts);
+        while unlocked < deposited {
+            let Some(next) = self.next_unlock(time) else {
+                break;
+            };
+            // between the start and the end `step` tokens unlock every period until the deposit
+            // is reached, sum such a run as an arithmetic series after checking its ends
+            if next > self.start_time() && next < self.end_time && step > 0 {
+                let by_time = (self.end_time - 1 - next) / self.period + 1;
+                let by_amount = (deposited - unlocked) / step;
+                let n = by_time.min(by_amount);
+                let last = next + n.saturating_sub(1) * self.period;
+                if n > 1
+                    && self.unlocked_at(next) == unlocked + step
+                    && self.unlocked_at(last) == unlocked + n * step
+                {
+                    let n128 = u128::from(n);
+                    weight += u128::from(step)
+                        * (n128 * u128::from(next - ts)
+                            + u128::from(self.period) * n128 * (n128 - 1) / 2);
+                    unlocked += n * step;
+                    time = last;
+                    continue;
+                }
+            }
+            let unlocked_next = self.unlocked_at(next);
+            weight += u128::from(unlocked_next - unlocked) * u128::from(next - ts);
+            unlocked = unlocked_next;
+            time = next;
+        }
+        weight
+    }
+}
@@ -0,0 +1,160 @@
+//! Zero-copy reads of Stream metadata account data
+//!
+//! The layout of `Contract` has no variable length fields, so every field is at a fixed offset.
+//! `ContractView` reads only the fields it's asked for, on-chain programs can check a Stream and
+//! run the vesting math without deserializing the 1104 byte account.
+
+use solana_pubkey::Pubkey;
+
+use crate::vesting::Vesting;
+
+/// Length of a serialized `Contract`, metadata accounts are padded to `state::METADATA_LEN`
+pub const CONTRACT_LEN: usize = 779;
+
+/// Offsets of `Contract` fields in account data
+pub mod offset {
+    pub const MAGIC: usize = 0;
+    pub const VERSION: usize = 8;
+    pub const CREATED_AT: usize = 9;
+    pub const AMOUNT_WITHDRAWN: usize = 17;
+    pub const CANCELED_AT: usize = 25;
+    pub const END_TIME: usize = 33;
+    pub const LAST_WITHDRAWN_AT: usize = 41;
+    pub const SENDER: usize = 49;
+    pub const SENDER_TOKENS: usize = 81;
+    pub const RECIPIENT: usize = 113;
+    pub const RECIPIENT_TOKENS: usize = 145;
+    pub const MINT: usize = 177;
+    pub const ESCROW_TOKENS: usize = 209;
+    pub const STREAMFLOW_FEE_TOTAL: usize = 305;
+    pub const PARTNER_FEE_TOTAL: usize = 389;
+    /// Start of `ix: CreateParams`
+    pub const IX: usize = 409;
+    pub const START_TIME: usize = IX;
+    pub const NET_AMOUNT_DEPOSITED: usize = IX + 8;
+    pub const PERIOD: usize = IX + 16;
+    pub const AMOUNT_PER_PERIOD: usize = IX + 24;
+    pub const CLIFF: usize = IX + 32;
+    pub const CLIFF_AMOUNT: usize = IX + 40;
+    pub const CLOSED: usize = 671;
+    pub const CURRENT_PAUSE_START: usize = 672;
+    pub const PAUSE_CUMULATIVE: usize = 680;
+    pub const LAST_RATE_CHANGE_TIME: usize = 688;
+    pub const FUNDS_UNLOCKED_AT_LAST_RATE_CHANGE: usize = 696;
+}
+
+/// Stream metadata account data read in place.
+///
+/// Doesn't check the account owner or `magic`, check that the account is owned by the Streamflow
+/// program before trusting the Stream.
+#[derive(Clone, Copy, Debug)]
+pub struct ContractView<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ContractView<'a> {
+    /// `None` if `data` is shorter than a serialized `Contract`
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        (data.len() >= CONTRACT_LEN).then_some(Self { data })
+    }
+
+    fn u64_at(&self, offset: usize) -> u64 {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&self.data[offset..offset + 8]);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn pubkey_at(&self, offset: usize) -> Pubkey {
+        let mut bytes = [0; 32];
+        bytes.copy_from_slice(&self.data[offset..offset + 32]);
+        Pubkey::new_from_array(bytes)
+    }
+
+    pub fn magic(&self) -> u64 {
+        self.u64_at(offset::MAGIC)
+    }
+
+    pub fn version(&self) -> u8 {
+        self.data[offset::VERSION]
+    }
+
+    pub fn created_at(&self) -> u64 {
+        self.u64_at(offset::CREATED_AT)
+    }
+
+    pub fn amount_withdrawn(&self) -> u64 {
+        self.u64_at(offset::AMOUNT_WITHDRAWN)
+    }
+
+    pub fn canceled_at(&self) -> u64 {
+        self.u64_at(offset::CANCELED_AT)
+    }
+
+    pub fn end_time(&self) -> u64 {
+        self.u64_at(offset::END_TIME)
+    }
+
+    pub fn last_withdrawn_at(&self) -> u64 {
+        self.u64_at(offset::LAST_WITHDRAWN_AT)
+    }
+
+    pub fn sender(&self) -> Pubkey {
+        self.pubkey_at(offset::SENDER)
+    }
+
+    pub fn sender_tokens(&self) -> Pubkey {
+        self.pubkey_at(offset::SENDER_TOKENS)
+    }
+
+    pub fn recipient(&self) -> Pubkey {
+        self.pubkey_at(offset::RECIPIENT)
+    }
+
+    pub fn recipient_tokens(&self) -> Pubkey {
+        self.pubkey_at(offset::RECIPIENT_TOKENS)
+    }
+
+    pub fn mint(&self) -> Pubkey {
+        self.pubkey_at(offset::MINT)
+    }
+
+    pub fn escrow_tokens(&self) -> Pubkey {
+        self.pubkey_at(offset::ESCROW_TOKENS)
+    }
+
+    pub fn streamflow_fee_total(&self) -> u64 {
+        self.u64_at(offset::STREAMFLOW_FEE_TOTAL)
+    }
+
+    pub fn partner_fee_total(&self) -> u64 {
+        self.u64_at(offset::PARTNER_FEE_TOTAL)
+    }
+
+    pub fn net_amount_deposited(&self) -> u64 {
+        self.u64_at(offset::NET_AMOUNT_DEPOSITED)
+    }
+
+    pub fn closed(&self) -> bool {
+        self.data[offset::CLOSED] != 0
+    }
+
+    /// Fields the vesting math depends on, same as `Contract::vesting` of the decoded account
+    pub fn vesting(&self) -> Vesting {
+        Vesting {
+            start_time: self.u64_at(offset::START_TIME),
+            net_amount_deposited: self.net_amount_deposited(),
+            period: self.u64_at(offset::PERIOD),
+            amount_per_period: self.u64_at(offset::AMOUNT_PER_PERIOD),
+            cliff: self.u64_at(offset::CLIFF),
+            cliff_amount: self.u64_at(offset::CLIFF_AMOUNT),
+            end_time: self.end_time(),
+            closed: self.closed(),
+            canceled_at: self.canceled_at(),
+            current_pause_start: self.u64_at(offset::CURRENT_PAUSE_START),
+            pause_cumulative: self.u64_at(offset::PAUSE_CUMULATIVE),
+            last_rate_change_time: self.u64_at(offset::LAST_RATE_CHANGE_TIME),
+            funds_unlocked_at_last_rate_change: self
+                .u64_at(offset::FUNDS_UNLOCKED_AT_LAST_RATE_CHANGE),
+        }
+    }
+}
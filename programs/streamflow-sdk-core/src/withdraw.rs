@@ -0,0 +1,129 @@
+//! Off-chain preview of withdrawals
+
+use core::fmt;
+
+use crate::state::{calculate_fee_from_amount, Contract};
+
+/// Reasons why a withdrawal would be rejected
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WithdrawError {
+    /// Stream is already closed
+    StreamClosed,
+    /// Requested amount is 0
+    ZeroAmount,
+    /// Requested amount is above the amount available to the recipient
+    AmountExceedsAvailable { requested: u64, available: u64 },
+}
+
+impl fmt::Display for WithdrawError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WithdrawError::StreamClosed => f.write_str("stream is closed"),
+            WithdrawError::ZeroAmount => f.write_str("amount can't be 0"),
+            WithdrawError::AmountExceedsAvailable {
+                requested,
+                available,
+            } => write!(
+                f,
+                "requested {requested} tokens, only {available} are available"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for WithdrawError {}
+
+/// Token transfers of a withdrawal and the resulting Stream
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WithdrawPreview {
+    /// Tokens sent to `recipient_tokens`
+    pub recipient_amount: u64,
+    /// Tokens sent to `streamflow_treasury_tokens`
+    pub streamflow_fee: u64,
+    /// Tokens sent to `partner_tokens`
+    pub partner_fee: u64,
+    /// Contract state after the withdrawal
+    pub contract: Contract,
+}
+
+impl Contract {
+    /// Fee unlocked by `now` and not yet withdrawn, fees unlock proportionally to the Stream.
+    ///
+    /// Matches the fees of the cancel test of the program quoted in `protocol_audit.pdf`, see
+    /// `protocol_cancel_vector` in the `streamflow-sdk` tests. That vector doesn't show whether the
+    /// program rounds the fee of the cliff and of the streamed amount separately, so previews may
+    /// be one unit off the program.
+    pub(crate) fn fee_available(
+        &self,
+        now: u64,
+        total: u64,
+        withdrawn: u64,
+        percentage: f32,
+    ) -> u64 {
+        if self.start_time() > now {
+            return 0;
+        }
+        let unlocked = if now >= self.end_time && self.current_pause_start == 0 {
+            total
+        } else {
+            let vested = calculate_fee_from_amount(self.vested_available(now), percentage);
+            let cliff = calculate_fee_from_amount(self.cliff_available(now), percentage);
+            total.min(vested + cliff)
+        };
+        unlocked.saturating_sub(withdrawn)
+    }
+
+    /// Preview a `withdraw` of `amount` at `now`, `u64::MAX` withdraws everything available.
+    ///
+    /// Treasury and partner fees unlocked so far are withdrawn together with the recipient amount.
+    pub fn preview_withdraw(
+        &self,
+        amount: u64,
+        now: u64,
+    ) -> Result<WithdrawPreview, WithdrawError> {
+        if self.closed {
+            return Err(WithdrawError::StreamClosed);
+        }
+        let available = self.available_to_claim(now, 100.0);
+        let recipient_amount = if amount == u64::MAX {
+            available
+        } else {
+            amount
+        };
+        if recipient_amount == 0 {
+            return Err(WithdrawError::ZeroAmount);
+        }
+        if recipient_amount > available {
+            return Err(WithdrawError::AmountExceedsAvailable {
+                requested: recipient_amount,
+                available,
+            });
+        }
+
+        let streamflow_fee = self.fee_available(
+            now,
+            self.streamflow_fee_total,
+            self.streamflow_fee_withdrawn,
+            self.streamflow_fee_percent,
+        );
+        let partner_fee = self.fee_available(
+            now,
+            self.partner_fee_total,
+            self.partner_fee_withdrawn,
+            self.partner_fee_percent,
+        );
+
+        let mut contract = self.clone();
+        contract.amount_withdrawn += recipient_amount;
+        contract.last_withdrawn_at = now;
+        contract.streamflow_fee_withdrawn += streamflow_fee;
+        contract.partner_fee_withdrawn += partner_fee;
+        Ok(WithdrawPreview {
+            recipient_amount,
+            streamflow_fee,
+            partner_fee,
+            contract,
+        })
+    }
+}
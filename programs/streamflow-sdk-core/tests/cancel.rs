@@ -0,0 +1,43 @@
+mod common;
+
+use common::DAY;
+use streamflow_sdk_core::cancel::CancelError;
+use streamflow_sdk_core::state::Contract;
+
+const START: u64 = 1_700_000_000;
+const CLIFF: u64 = START + 10 * DAY;
+
+/// 100 tokens at the cliff, then 10 a day for 90 days
+fn stream() -> Contract {
+    common::contract(START, 1_000, DAY, 10, CLIFF, 100)
+}
+
+#[test]
+fn splits_deposit() {
+    let mut contract = stream();
+    contract.amount_withdrawn = 50;
+    let now = CLIFF + 5 * DAY;
+
+    let preview = contract.preview_cancel(now).unwrap();
+    assert_eq!(preview.recipient_amount, 100);
+    assert_eq!(preview.sender_amount, 850);
+    assert_eq!(preview.contract.amount_withdrawn, 150);
+    assert_eq!(preview.contract.canceled_at, now);
+    assert!(preview.contract.closed);
+    assert_eq!(
+        preview.contract.preview_cancel(now).unwrap_err(),
+        CancelError::StreamClosed
+    );
+}
+
+#[test]
+fn saturates_inconsistent_accounts() {
+    // more withdrawn than deposited, as in corrupt account data
+    let mut contract = stream();
+    contract.amount_withdrawn = 2_000;
+    contract.streamflow_fee_withdrawn = u64::MAX;
+    let preview = contract.preview_cancel(CLIFF + 5 * DAY).unwrap();
+    assert_eq!(preview.recipient_amount, 0);
+    assert_eq!(preview.sender_amount, 0);
+    assert_eq!(preview.contract.streamflow_fee_withdrawn, u64::MAX);
+}
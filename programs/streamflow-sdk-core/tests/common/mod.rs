@@ -0,0 +1,105 @@
+//! Streams shared by the tests of `streamflow-sdk-core` and `streamflow-sdk`, the latter includes
+//! this module by path
+
+#![allow(dead_code)]
+
+use streamflow_sdk_core::state::{Contract, CreateParams, CREATE_PARAMS_PADDING};
+use streamflow_sdk_core::versioned::VersionedContract;
+
+pub const METADATA_V1: &[u8] =
+    include_bytes!("../../../streamflow-sdk/tests/fixtures/metadata_v1.bin");
+pub const METADATA_V2: &[u8] =
+    include_bytes!("../../../streamflow-sdk/tests/fixtures/metadata_v2.bin");
+pub const METADATA_V3: &[u8] =
+    include_bytes!("../../../streamflow-sdk/tests/fixtures/metadata_v3.bin");
+
+pub const DAY: u64 = 86_400;
+
+/// Stream of `METADATA_V3`
+pub fn fixture() -> Contract {
+    VersionedContract::decode(METADATA_V3).unwrap().contract
+}
+
+/// Fixture without rate changes and pauses, its whole history is recorded
+pub fn recorded() -> Contract {
+    let mut contract = fixture();
+    contract.last_rate_change_time = 0;
+    contract.funds_unlocked_at_last_rate_change = 0;
+    contract.pause_cumulative = 0;
+    contract
+}
+
+/// Stream without fees, pauses and rate changes, `end_time` is when the last period unlocks
+pub fn contract(
+    start_time: u64,
+    net_amount_deposited: u64,
+    period: u64,
+    amount_per_period: u64,
+    cliff: u64,
+    cliff_amount: u64,
+) -> Contract {
+    let ix = CreateParams {
+        start_time,
+        net_amount_deposited,
+        period,
+        amount_per_period,
+        cliff,
+        cliff_amount,
+        cancelable_by_sender: true,
+        cancelable_by_recipient: false,
+        automatic_withdrawal: false,
+        transferable_by_sender: true,
+        transferable_by_recipient: false,
+        can_topup: false,
+        stream_name: [0; 64],
+        withdraw_frequency: 0,
+        ghost: 0,
+        pausable: true,
+        can_update_rate: true,
+        ghost2: 0,
+        is_pda: false,
+        nonce: 0,
+    };
+    let mut contract = Contract {
+        magic: 0,
+        version: 0,
+        created_at: start_time,
+        amount_withdrawn: 0,
+        canceled_at: 0,
+        end_time: 0,
+        last_withdrawn_at: 0,
+        sender: Default::default(),
+        sender_tokens: Default::default(),
+        recipient: Default::default(),
+        recipient_tokens: Default::default(),
+        mint: Default::default(),
+        escrow_tokens: Default::default(),
+        streamflow_treasury: Default::default(),
+        streamflow_treasury_tokens: Default::default(),
+        streamflow_fee_total: 0,
+        streamflow_fee_withdrawn: 0,
+        streamflow_fee_percent: 0.0,
+        partner: Default::default(),
+        partner_tokens: Default::default(),
+        partner_fee_total: 0,
+        partner_fee_withdrawn: 0,
+        partner_fee_percent: 0.0,
+        ix,
+        ix_padding: [0; CREATE_PARAMS_PADDING],
+        closed: false,
+        current_pause_start: 0,
+        pause_cumulative: 0,
+        last_rate_change_time: 0,
+        funds_unlocked_at_last_rate_change: 0,
+        creation_fee: 0,
+        creation_fee_claimed: false,
+        auto_claim_fee: 0,
+        auto_claim_fee_claimed: false,
+        old_metadata: Default::default(),
+        payer: Default::default(),
+        bump: 0,
+    };
+    let periods = (net_amount_deposited - cliff_amount).div_ceil(amount_per_period);
+    contract.end_time = contract.start_time() + periods * period;
+    contract
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use common::METADATA_V3;
+use solana_pubkey::Pubkey;
+use streamflow_sdk_core::state::{
+    derive_metadata, find_escrow_account, pad_stream_name, trim_stream_name, Cluster, Contract,
+};
+use streamflow_sdk_core::versioned::VersionedContract;
+
+#[test]
+fn decodes_without_anchor() {
+    let stream = VersionedContract::decode(METADATA_V3).unwrap();
+    assert_eq!(stream.nonce(), Some(stream.contract.ix.nonce));
+
+    let contract = stream.contract;
+    let serialized = contract.try_to_vec().unwrap();
+    assert_eq!(serialized, METADATA_V3[..serialized.len()]);
+    let decoded = Contract::try_from_slice(&serialized).unwrap();
+    assert_eq!(decoded.sender, contract.sender);
+    assert_eq!(decoded.ix.stream_name, contract.ix.stream_name);
+
+    let program_id = Cluster::Mainnet.program_id();
+    let (metadata, bump) = derive_metadata(
+        &contract.mint,
+        &contract.payer,
+        contract.ix.nonce,
+        &program_id,
+    );
+    let seeds: &[&[u8]] = &[
+        b"strm-met",
+        contract.mint.as_ref(),
+        contract.payer.as_ref(),
+        &contract.ix.nonce.to_be_bytes(),
+        &[bump],
+    ];
+    assert_eq!(
+        Pubkey::create_program_address(seeds, &program_id).unwrap(),
+        metadata
+    );
+    let (escrow, bump) = find_escrow_account(metadata.as_ref(), &program_id);
+    let seeds: &[&[u8]] = &[b"strm", metadata.as_ref(), &[bump]];
+    assert_eq!(
+        Pubkey::create_program_address(seeds, &program_id).unwrap(),
+        escrow
+    );
+}
+
+#[test]
+fn pads_and_trims_stream_name() {
+    let name = pad_stream_name("a\0b").unwrap();
+    assert_eq!(name[..4], *b"a\0b\0");
+    assert_eq!(trim_stream_name(&name), "a\0b");
+    assert_eq!(trim_stream_name(&[0; 64]), "");
+    assert_eq!(pad_stream_name(&"x".repeat(64)).unwrap(), [b'x'; 64]);
+    assert_eq!(pad_stream_name(&"x".repeat(65)), None);
+}
@@ -0,0 +1,153 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::{fixture, DAY};
+use solana_pubkey::Pubkey;
+use streamflow_sdk_core::portfolio::{Bucket, Portfolio};
+
+/// After the last rate change of the fixture
+const NOW: u64 = 1_710_000_000;
+
+fn portfolio() -> (Portfolio, Pubkey, Pubkey) {
+    let open = fixture();
+    let mut canceled = fixture();
+    canceled.closed = true;
+    canceled.canceled_at = NOW - 1;
+    let mut other = fixture();
+    other.mint = Pubkey::new_unique();
+
+    let (mint, other_mint) = (open.mint, other.mint);
+    let streams = [open, canceled, other].map(|c| (Pubkey::new_unique(), c));
+    (Portfolio::new(streams), mint, other_mint)
+}
+
+#[test]
+fn totals_per_mint() {
+    let (portfolio, mint, other_mint) = portfolio();
+    let totals = portfolio.totals(NOW);
+    assert_eq!(totals.len(), 2);
+
+    let c = fixture();
+    let unlocked = c.unlocked_at(NOW);
+    let single = totals[&other_mint];
+    assert_eq!(single.streams, 1);
+    assert_eq!(single.vested, unlocked);
+    assert_eq!(single.claimable, c.available_to_claim(NOW, 100.0));
+    assert_eq!(single.locked, c.ix.net_amount_deposited - unlocked);
+    assert_eq!(single.returned, 0);
+    assert_eq!(single.streamflow_fee, c.streamflow_fee_total);
+
+    let both = totals[&mint];
+    assert_eq!(both.streams, 2);
+    assert_eq!(both.deposited, 2 * c.ix.net_amount_deposited);
+    assert_eq!(both.withdrawn, 2 * c.amount_withdrawn);
+    assert_eq!(both.claimable, single.claimable);
+    assert_eq!(both.locked, single.locked);
+    assert_eq!(
+        both.returned,
+        c.ix.net_amount_deposited - c.amount_withdrawn
+    );
+    assert_eq!(both.deposited, both.vested + both.locked + both.returned);
+}
+
+#[test]
+fn buckets_follow_schedule() {
+    let (portfolio, mint, other_mint) = portfolio();
+    let c = fixture();
+    let expected: u64 = c.schedule(NOW).map(|u| u.amount).sum();
+
+    for bucket in [Bucket::Day, Bucket::Week, Bucket::Month] {
+        let schedules = portfolio.unlock_schedule(NOW, bucket);
+        for key in [mint, other_mint] {
+            let unlocks = &schedules[&key];
+            assert_eq!(unlocks.iter().map(|u| u.amount).sum::<u64>(), expected);
+            assert!(unlocks.windows(2).all(|w| w[0].start < w[1].start));
+            assert!(unlocks.iter().all(|u| bucket.start(u.start) == u.start));
+        }
+    }
+
+    let monthly = portfolio.unlock_schedule(NOW, Bucket::Month);
+    let first = monthly[&mint][0];
+    let in_first_month: u64 = c
+        .schedule(NOW)
+        .take_while(|u| u.time < Bucket::Month.next(first.start))
+        .map(|u| u.amount)
+        .sum();
+    assert_eq!(first.amount, in_first_month);
+}
+
+#[test]
+fn calendar_buckets() {
+    // 2024-02-29 12:00:00 UTC, a Thursday
+    let time = 1_709_208_000;
+    assert_eq!(Bucket::Day.start(time), 1_709_164_800);
+    // Monday 2024-02-26
+    assert_eq!(Bucket::Week.start(time), 1_708_905_600);
+    // 2024-02-01 and 2024-03-01
+    assert_eq!(Bucket::Month.start(time), 1_706_745_600);
+    assert_eq!(Bucket::Month.next(1_706_745_600), 1_709_251_200);
+    // 2024-12-01 to 2025-01-01
+    assert_eq!(Bucket::Month.next(1_733_011_200), 1_735_689_600);
+}
+
+#[test]
+fn paused_stream() {
+    let reference = fixture();
+    let mut paused = fixture();
+    let pause_start = NOW + 30 * DAY;
+    paused.current_pause_start = pause_start;
+    let portfolio = Portfolio::new([(Pubkey::new_unique(), paused.clone())]);
+
+    // before the pause start a paused Stream unlocks like an unpaused one
+    let totals = portfolio.totals(NOW)[&paused.mint];
+    assert_eq!(totals.vested, reference.unlocked_at(NOW));
+    assert_eq!(
+        totals.deposited,
+        totals.vested + totals.locked + totals.returned
+    );
+
+    // and stops unlocking at the pause start
+    let unlocked = reference.unlocked_at(pause_start);
+    let totals = portfolio.totals(pause_start + 90 * DAY)[&paused.mint];
+    assert_eq!(totals.vested, unlocked);
+    assert_eq!(totals.locked, paused.ix.net_amount_deposited - unlocked);
+
+    let schedule = &portfolio.unlock_schedule(NOW, Bucket::Week)[&paused.mint];
+    assert_eq!(
+        schedule.iter().map(|u| u.amount).sum::<u64>(),
+        unlocked - reference.unlocked_at(NOW)
+    );
+    assert!(schedule.iter().all(|u| u.start <= pause_start));
+    assert!(portfolio
+        .unlock_schedule(pause_start, Bucket::Week)
+        .is_empty());
+}
+
+#[test]
+fn closed_stream() {
+    // fully withdrawn and closed without a cancel
+    let mut closed = fixture();
+    closed.closed = true;
+    closed.amount_withdrawn = closed.ix.net_amount_deposited;
+    let portfolio = Portfolio::new([(Pubkey::new_unique(), closed.clone())]);
+
+    let totals = portfolio.totals(NOW)[&closed.mint];
+    assert_eq!(totals.vested, closed.ix.net_amount_deposited);
+    assert_eq!(totals.withdrawn, closed.ix.net_amount_deposited);
+    assert_eq!(totals.claimable, 0);
+    assert_eq!(totals.locked, 0);
+    assert_eq!(totals.returned, 0);
+    assert!(portfolio.unlock_schedule(NOW, Bucket::Day).is_empty());
+
+    // sums saturate instead of overflowing
+    let mut over = closed;
+    over.closed = false;
+    over.amount_withdrawn = u64::MAX;
+    let totals = Portfolio::new([
+        (Pubkey::new_unique(), over.clone()),
+        (Pubkey::new_unique(), over),
+    ])
+    .totals(NOW);
+    assert_eq!(totals.values().next().unwrap().withdrawn, u64::MAX);
+}
@@ -0,0 +1,152 @@
+#![cfg(feature = "std")]
+
+mod common;
+
+use common::{fixture, recorded, DAY};
+use streamflow_sdk_core::portfolio::Bucket;
+use streamflow_sdk_core::report::{to_csv, ReportRow, Withdrawal, CSV_HEADER};
+
+fn sum(rows: &[ReportRow], field: fn(&ReportRow) -> u64) -> u64 {
+    rows.iter().map(field).sum()
+}
+
+#[test]
+fn recognizes_whole_deposit() {
+    for contract in [recorded(), fixture()] {
+        let boundaries = Bucket::Month.boundaries(contract.created_at, contract.end_time + 1);
+        let rows = contract.accrual_report(&boundaries, &[]);
+        assert_eq!(rows.len(), boundaries.len() - 1);
+        assert_eq!(sum(&rows, |r| r.vested), contract.ix.net_amount_deposited);
+        assert_eq!(sum(&rows, |r| r.cliff), contract.ix.cliff_amount);
+        assert_eq!(
+            sum(&rows, |r| r.streamflow_fee),
+            contract.streamflow_fee_total
+        );
+        assert_eq!(sum(&rows, |r| r.partner_fee), contract.partner_fee_total);
+
+        // the cliff is recognized as a lump in the period containing it
+        let cliff_row = rows
+            .iter()
+            .find(|r| (r.start..r.end).contains(&contract.ix.cliff))
+            .unwrap();
+        assert_eq!(cliff_row.cliff, contract.ix.cliff_amount);
+    }
+}
+
+#[test]
+fn follows_vesting_math() {
+    let contract = recorded();
+    let boundaries = Bucket::Day.boundaries(contract.created_at, contract.end_time + 1);
+    for row in contract.accrual_report(&boundaries, &[]) {
+        let expected = contract.unlocked_at(row.end - 1) - contract.unlocked_at(row.start - 1);
+        assert_eq!(row.vested, expected);
+        assert!(!row.estimated);
+    }
+}
+
+#[test]
+fn marks_unrecorded_history() {
+    let contract = fixture();
+    let estimated_until = contract.last_rate_change_time + contract.pause_cumulative;
+    let boundaries = Bucket::Week.boundaries(contract.created_at, contract.end_time + 1);
+    let rows = contract.accrual_report(&boundaries, &[]);
+
+    let (estimated, exact): (Vec<&ReportRow>, Vec<&ReportRow>) =
+        rows.iter().partition(|r| r.estimated);
+    assert!(!estimated.is_empty());
+    assert!(estimated.iter().all(|r| r.end > contract.start_time()));
+    assert!(estimated.iter().all(|r| r.start < estimated_until));
+    // amounts unlocked before the last rate change are exact in total
+    let vested: u64 = estimated.iter().map(|r| r.vested).sum();
+    let last = estimated.last().unwrap();
+    assert_eq!(
+        vested,
+        contract.unlocked_at(last.end - 1) - contract.unlocked_at(contract.start_time() - 1)
+    );
+    assert!(exact
+        .iter()
+        .all(|r| r.end <= contract.start_time() || r.start >= estimated_until));
+}
+
+#[test]
+fn stops_at_cancel_and_sums_withdrawals() {
+    let now = 1_712_000_000;
+    let contract = recorded();
+    let canceled = contract.preview_cancel(now).unwrap().contract;
+    let boundaries = Bucket::Month.boundaries(contract.created_at, contract.end_time + 1);
+
+    let withdrawals = [
+        Withdrawal {
+            time: contract.ix.cliff + DAY,
+            amount: 100,
+        },
+        Withdrawal {
+            time: contract.ix.cliff + 2 * DAY,
+            amount: 50,
+        },
+        Withdrawal {
+            time: now,
+            amount: 7,
+        },
+    ];
+    let rows = canceled.accrual_report(&boundaries, &withdrawals);
+    assert_eq!(sum(&rows, |r| r.vested), contract.unlocked_at(now));
+    assert!(rows.iter().filter(|r| r.start > now).all(|r| r.vested == 0));
+    assert_eq!(sum(&rows, |r| r.withdrawn), 157);
+    let cliff_row = rows
+        .iter()
+        .find(|r| (r.start..r.end).contains(&(contract.ix.cliff + DAY)))
+        .unwrap();
+    assert_eq!(cliff_row.withdrawn, 150);
+}
+
+#[test]
+fn exports_csv() {
+    let row = ReportRow {
+        start: 1_700_000_000,
+        end: 1_700_086_400,
+        vested: 10,
+        cliff: 4,
+        withdrawn: 3,
+        streamflow_fee: 2,
+        partner_fee: 1,
+        estimated: false,
+    };
+    assert_eq!(row.to_csv(), "1700000000,1700086400,10,4,3,2,1,false");
+    assert_eq!(
+        to_csv(&[row, row]),
+        format!("{CSV_HEADER}\n{}\n{}\n", row.to_csv(), row.to_csv())
+    );
+    assert_eq!(
+        CSV_HEADER.split(',').count(),
+        row.to_csv().split(',').count()
+    );
+}
+
+#[test]
+fn stops_at_active_pause() {
+    let reference = recorded();
+    let mut contract = recorded();
+    let pause_start = contract.ix.cliff + 60 * DAY;
+    contract.current_pause_start = pause_start;
+
+    // across the pause start
+    let boundaries = Bucket::Month.boundaries(contract.created_at, contract.end_time + 1);
+    let rows = contract.accrual_report(&boundaries, &[]);
+    assert_eq!(sum(&rows, |r| r.vested), reference.unlocked_at(pause_start));
+    assert!(rows
+        .iter()
+        .filter(|r| r.start > pause_start)
+        .all(|r| r.vested == 0 && r.streamflow_fee == 0));
+    for (row, expected) in rows.iter().zip(reference.accrual_report(&boundaries, &[])) {
+        if row.end <= pause_start {
+            assert_eq!(*row, expected);
+        }
+    }
+
+    // inside the pause
+    let boundaries = Bucket::Day.boundaries(pause_start + DAY, pause_start + 30 * DAY);
+    let rows = contract.accrual_report(&boundaries, &[]);
+    assert_eq!(rows.len(), boundaries.len() - 1);
+    assert!(rows.iter().all(|r| r.vested == 0 && r.cliff == 0));
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "serde")]
+
+mod common;
+
+use borsh::BorshSerialize;
+use common::{fixture, METADATA_V1, METADATA_V2, METADATA_V3};
+use streamflow_sdk_core::state::{Contract, CREATE_PARAMS_PADDING};
+use streamflow_sdk_core::versioned::VersionedContract;
+
+fn round_trip(contract: &Contract) -> Contract {
+    serde_json::from_str(&serde_json::to_string(contract).unwrap()).unwrap()
+}
+
+#[test]
+fn round_trips_fixtures() {
+    for data in [METADATA_V1, METADATA_V2, METADATA_V3] {
+        let versioned = VersionedContract::decode(data).unwrap();
+        let json = serde_json::to_value(&versioned).unwrap();
+        assert_eq!(json["contract"]["ix"]["stream_name"], "Team vesting Q1");
+        assert_eq!(
+            json["contract"]["mint"],
+            versioned.contract.mint.to_string()
+        );
+
+        let decoded: VersionedContract = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            decoded.contract.try_to_vec().unwrap(),
+            versioned.contract.try_to_vec().unwrap()
+        );
+    }
+}
+
+#[test]
+fn skips_ix_padding() {
+    let mut contract = fixture();
+    contract.ix_padding = [7; CREATE_PARAMS_PADDING];
+    let json = serde_json::to_value(&contract).unwrap();
+    assert!(json.get("ix_padding").is_none());
+    // padding isn't part of the JSON, it deserializes as zeros
+    assert_eq!(round_trip(&contract).ix_padding, [0; CREATE_PARAMS_PADDING]);
+}
+
+#[test]
+fn trims_stream_name() {
+    let mut contract = fixture();
+
+    // zero padding is trimmed and restored
+    let name = round_trip(&contract).ix.stream_name;
+    assert_eq!(name, contract.ix.stream_name);
+
+    // zeros inside the name are kept, only trailing ones are trimmed
+    contract.ix.stream_name = [0; 64];
+    contract.ix.stream_name[..3].copy_from_slice(b"a\0b");
+    let json = serde_json::to_value(&contract).unwrap();
+    assert_eq!(json["ix"]["stream_name"], "a\u{0}b");
+    assert_eq!(
+        round_trip(&contract).ix.stream_name,
+        contract.ix.stream_name
+    );
+
+    // invalid UTF-8 is lossy, it's replaced and doesn't round trip
+    contract.ix.stream_name = [0; 64];
+    contract.ix.stream_name[..2].copy_from_slice(&[b'a', 0xff]);
+    let json = serde_json::to_value(&contract).unwrap();
+    assert_eq!(json["ix"]["stream_name"], "a\u{fffd}");
+    assert_eq!(
+        &round_trip(&contract).ix.stream_name[..4],
+        "a\u{fffd}".as_bytes()
+    );
+
+    // names longer than 64 bytes are rejected
+    let mut json = json;
+    json["ix"]["stream_name"] = "x".repeat(65).into();
+    assert!(serde_json::from_value::<Contract>(json).is_err());
+}
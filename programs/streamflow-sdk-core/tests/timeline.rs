@@ -0,0 +1,158 @@
+mod common;
+
+use common::DAY;
+use streamflow_sdk_core::state::Contract;
+use streamflow_sdk_core::timeline::{PauseRule, StreamStatus};
+
+const START: u64 = 1_700_000_000;
+const CLIFF: u64 = START + 10 * DAY;
+
+/// 100 tokens at the cliff, then 10 a day for 90 days
+fn stream() -> Contract {
+    common::contract(START, 1_000, DAY, 10, CLIFF, 100)
+}
+
+fn paused_at(pause_start: u64) -> Contract {
+    let mut contract = stream();
+    contract.current_pause_start = pause_start;
+    contract
+}
+
+#[test]
+fn not_paused() {
+    let mut contract = stream();
+    // an earlier pause of two days, already unpaused
+    contract.pause_cumulative = 2 * DAY;
+    contract.end_time += 2 * DAY;
+    let now = CLIFF + 5 * DAY;
+
+    let timeline = contract.timeline(now);
+    assert_eq!(timeline.rule, PauseRule::NotPaused);
+    assert!(!timeline.is_paused());
+    assert_eq!(timeline.start_time, CLIFF);
+    assert_eq!(timeline.effective_start_time, CLIFF);
+    assert_eq!(timeline.past_paused_seconds, 2 * DAY);
+    assert_eq!(timeline.paused_seconds, 2 * DAY);
+    assert_eq!(timeline.current_pause_seconds(), 0);
+    assert_eq!(timeline.end_time, contract.end_time);
+}
+
+#[test]
+fn pre_start_pause() {
+    let contract = paused_at(START - DAY);
+    let now = START + 5 * DAY;
+
+    let timeline = contract.timeline(now);
+    assert_eq!(timeline.rule, PauseRule::PreStartPause);
+    assert_eq!(timeline.current_pause_start, Some(START - DAY));
+    assert_eq!(timeline.current_pause_seconds(), 6 * DAY);
+    assert_eq!(timeline.end_time, contract.end_time + 6 * DAY);
+    assert_eq!(contract.unlocked_at(CLIFF + DAY), 0);
+    assert_eq!(contract.next_unlock(START - 1), None);
+}
+
+#[test]
+fn in_cliff_pause() {
+    let contract = paused_at(START + DAY);
+    let now = CLIFF + DAY;
+
+    let timeline = contract.timeline(now);
+    assert_eq!(timeline.rule, PauseRule::InCliffPause);
+    assert_eq!(timeline.current_pause_seconds(), 10 * DAY);
+    assert_eq!(timeline.end_time, contract.end_time + 10 * DAY);
+    // neither the cliff nor the streamed amount unlock
+    assert_eq!(contract.unlocked_at(now), 0);
+    assert_eq!(contract.next_unlock(START), None);
+}
+
+#[test]
+fn mid_stream_pause() {
+    let pause_start = CLIFF + 5 * DAY;
+    let mut contract = paused_at(pause_start);
+    contract.pause_cumulative = DAY;
+    contract.end_time += DAY;
+    let now = pause_start + 3 * DAY;
+
+    let timeline = contract.timeline(now);
+    assert_eq!(timeline.rule, PauseRule::MidStreamPause);
+    assert_eq!(timeline.past_paused_seconds, DAY);
+    assert_eq!(timeline.paused_seconds, 4 * DAY);
+    assert_eq!(timeline.current_pause_seconds(), 3 * DAY);
+    // the active pause delays the end by its duration so far
+    assert_eq!(timeline.end_time, contract.end_time + 3 * DAY);
+    assert_eq!(contract.unlocked_at(now), contract.unlocked_at(pause_start));
+    assert_eq!(contract.next_unlock(now), None);
+
+    let display = timeline.to_string();
+    assert!(display.contains(&format!("paused since {pause_start} for {}", 3 * DAY)));
+    assert!(display.ends_with(&PauseRule::MidStreamPause.to_string()));
+}
+
+#[test]
+fn unpaused_stream_ends_at_extended_end() {
+    let pause_start = CLIFF + 5 * DAY;
+    let now = pause_start + 3 * DAY;
+    let end_time = paused_at(pause_start).timeline(now).end_time;
+
+    // unpausing at `now` adds the pause to the cumulative time and the end
+    let mut contract = stream();
+    contract.pause_cumulative = 3 * DAY;
+    contract.end_time = end_time;
+    let unlocked = |ts| contract.vested_available(ts) + contract.cliff_available(ts);
+    assert_eq!(unlocked(end_time - 1), 990);
+    assert_eq!(unlocked(end_time), 1_000);
+    assert_eq!(contract.unlocked_at(end_time - 1), 990);
+    assert_eq!(contract.unlocked_at(end_time), 1_000);
+}
+
+#[test]
+fn status() {
+    let contract = stream();
+    // unlocks begin at the cliff
+    assert_eq!(contract.status(START + DAY), StreamStatus::Scheduled);
+    assert_eq!(contract.status(CLIFF), StreamStatus::Streaming);
+    assert_eq!(
+        contract.status(contract.end_time - 1),
+        StreamStatus::Streaming
+    );
+    assert_eq!(contract.status(contract.end_time), StreamStatus::Completed);
+
+    let mut paused = paused_at(CLIFF + DAY);
+    assert_eq!(paused.status(CLIFF + 2 * DAY), StreamStatus::Paused);
+    assert_eq!(paused.status(paused.end_time + DAY), StreamStatus::Paused);
+    paused.closed = true;
+    assert_eq!(paused.status(CLIFF + 2 * DAY), StreamStatus::Closed);
+    assert_eq!(StreamStatus::Closed.to_string(), "closed");
+}
+
+#[test]
+fn next_unlock() {
+    let contract = stream();
+    assert_eq!(contract.next_unlock(START), Some(CLIFF));
+    assert_eq!(contract.next_unlock(CLIFF), Some(CLIFF + DAY));
+    assert_eq!(contract.next_unlock(CLIFF + DAY / 2), Some(CLIFF + DAY));
+    assert_eq!(
+        contract.next_unlock(contract.end_time - 1),
+        Some(contract.end_time)
+    );
+    assert_eq!(contract.next_unlock(contract.end_time), None);
+
+    // past pauses shift the unlocks
+    let mut contract = stream();
+    contract.pause_cumulative = DAY / 2;
+    contract.end_time += DAY / 2;
+    let next = contract.next_unlock(CLIFF + DAY).unwrap();
+    assert_eq!(next, CLIFF + DAY + DAY / 2);
+    assert_eq!(contract.unlocked_at(next - 1), 100);
+    assert_eq!(contract.unlocked_at(next), 110);
+
+    // unlocks before a pause are returned, later ones aren't
+    let pause_start = CLIFF + 5 * DAY;
+    let paused = paused_at(pause_start);
+    assert_eq!(paused.next_unlock(pause_start - DAY / 2), Some(pause_start));
+    assert_eq!(paused.next_unlock(pause_start), None);
+
+    let mut closed = stream();
+    closed.closed = true;
+    assert_eq!(closed.next_unlock(CLIFF), None);
+}
@@ -0,0 +1,216 @@
+mod common;
+
+use borsh::BorshSerialize;
+use common::{recorded, DAY};
+use streamflow_sdk_core::state::Contract;
+use streamflow_sdk_core::update::{UpdateArgs, UpdateError};
+
+const NEW_RATE: u64 = 5_000_000;
+
+/// Fixture without rate changes and pauses, ending as if it wasn't paused
+fn fixture() -> Contract {
+    let mut contract = recorded();
+    contract.end_time -= common::fixture().pause_cumulative;
+    contract
+}
+
+fn set_rate(contract: &Contract, now: u64) -> Contract {
+    contract
+        .preview_update(UpdateArgs::new().set_rate(NEW_RATE), now)
+        .unwrap()
+        .contract
+}
+
+fn streamed(contract: &Contract) -> u64 {
+    contract.ix.net_amount_deposited - contract.ix.cliff_amount
+}
+
+#[test]
+fn rate_change_before_cliff() {
+    let contract = fixture();
+    let cliff = contract.ix.cliff;
+    let now = contract.ix.start_time + DAY;
+    let updated = set_rate(&contract, now);
+
+    assert_eq!(updated.ix.amount_per_period, NEW_RATE);
+    assert_eq!(updated.last_rate_change_time, now);
+    assert_eq!(updated.funds_unlocked_at_last_rate_change, 0);
+    // streaming still starts at the cliff
+    assert_eq!(updated.effective_start_time(), cliff);
+    assert_eq!(
+        updated.end_time,
+        cliff + streamed(&contract).div_ceil(NEW_RATE) * DAY
+    );
+    assert_eq!(updated.unlocked_at(cliff), contract.ix.cliff_amount);
+    assert_eq!(
+        updated.unlocked_at(cliff + DAY),
+        contract.ix.cliff_amount + NEW_RATE
+    );
+    assert_eq!(
+        updated.unlocked_at(updated.end_time),
+        contract.ix.net_amount_deposited
+    );
+}
+
+#[test]
+fn rate_change_mid_stream() {
+    let contract = fixture();
+    let rate = contract.ix.amount_per_period;
+    // periods started before the change don't count
+    let now = contract.ix.cliff + 10 * DAY + 100;
+    let updated = set_rate(&contract, now);
+
+    assert_eq!(updated.funds_unlocked_at_last_rate_change, 10 * rate);
+    assert_eq!(updated.unlocked_at(now), contract.unlocked_at(now));
+    assert_eq!(updated.effective_start_time(), now);
+    assert_eq!(
+        updated.unlocked_at(now + DAY - 1),
+        contract.unlocked_at(now)
+    );
+    assert_eq!(
+        updated.unlocked_at(now + DAY),
+        contract.unlocked_at(now) + NEW_RATE
+    );
+    assert_eq!(
+        updated.end_time,
+        now + (streamed(&contract) - 10 * rate).div_ceil(NEW_RATE) * DAY
+    );
+}
+
+#[test]
+fn rate_change_after_pause() {
+    let mut contract = fixture();
+    let rate = contract.ix.amount_per_period;
+    contract.pause_cumulative = 2 * DAY;
+    contract.end_time += 2 * DAY;
+    let now = contract.ix.cliff + 10 * DAY;
+    let updated = set_rate(&contract, now);
+
+    // the past pause delayed unlocks by 2 periods and is folded into the unlocked funds
+    assert_eq!(updated.funds_unlocked_at_last_rate_change, 8 * rate);
+    assert_eq!(updated.pause_cumulative, 0);
+    assert_eq!(updated.unlocked_at(now), contract.unlocked_at(now));
+}
+
+#[test]
+fn rate_change_after_end() {
+    let contract = fixture();
+    let now = contract.end_time + 30 * DAY;
+    let updated = set_rate(&contract, now);
+
+    // periods past the end don't unlock more than was deposited
+    assert_eq!(
+        updated.funds_unlocked_at_last_rate_change,
+        streamed(&contract)
+    );
+    assert_eq!(updated.end_time, now);
+    assert_eq!(
+        updated.unlocked_at(now + DAY),
+        contract.ix.net_amount_deposited
+    );
+}
+
+#[test]
+fn rate_change_while_paused() {
+    let mut contract = fixture();
+    contract.current_pause_start = contract.ix.cliff + 5 * DAY;
+    let now = contract.ix.cliff + 10 * DAY;
+    assert_eq!(
+        contract
+            .preview_update(UpdateArgs::new().set_rate(NEW_RATE), now)
+            .unwrap_err(),
+        UpdateError::RateUpdateWhilePaused
+    );
+
+    // other updates of a paused Stream keep the pause
+    let updated = contract
+        .preview_update(UpdateArgs::new().disable_sender_cancel(), now)
+        .unwrap()
+        .contract;
+    assert!(!updated.ix.cancelable_by_sender);
+    assert_eq!(updated.current_pause_start, contract.current_pause_start);
+}
+
+#[test]
+fn validates_only_refused_arguments() {
+    // no-ops on a Stream that already has these values
+    let no_ops = UpdateArgs {
+        enable_automatic_withdrawal: Some(false),
+        transferable_by_sender: Some(true),
+        transferable_by_recipient: Some(false),
+        cancelable_by_sender: Some(true),
+        ..UpdateArgs::default()
+    };
+    assert_eq!(no_ops.validate(), Ok(()));
+    assert_eq!(UpdateArgs::new().validate(), Ok(()));
+
+    let mut contract = fixture();
+    contract.ix.automatic_withdrawal = false;
+    contract.ix.transferable_by_sender = true;
+    contract.ix.transferable_by_recipient = false;
+    contract.ix.cancelable_by_sender = true;
+    let updated = contract.preview_update(no_ops, DAY).unwrap().contract;
+    assert_eq!(
+        updated.try_to_vec().unwrap(),
+        contract.try_to_vec().unwrap()
+    );
+
+    // the same flags change a Stream with the opposite values in a forbidden direction
+    let mut opposite = contract.clone();
+    opposite.ix.automatic_withdrawal = true;
+    opposite.ix.transferable_by_sender = false;
+    opposite.ix.transferable_by_recipient = true;
+    opposite.ix.cancelable_by_sender = false;
+    let cases = [
+        (
+            UpdateArgs {
+                enable_automatic_withdrawal: Some(false),
+                ..UpdateArgs::default()
+            },
+            UpdateError::AutomaticWithdrawalCannotBeDisabled,
+        ),
+        (
+            UpdateArgs {
+                transferable_by_sender: Some(true),
+                ..UpdateArgs::default()
+            },
+            UpdateError::SenderTransferCannotBeEnabled,
+        ),
+        (
+            UpdateArgs {
+                transferable_by_recipient: Some(false),
+                ..UpdateArgs::default()
+            },
+            UpdateError::RecipientTransferCannotBeDisabled,
+        ),
+        (
+            UpdateArgs {
+                cancelable_by_sender: Some(true),
+                ..UpdateArgs::default()
+            },
+            UpdateError::SenderCancelCannotBeEnabled,
+        ),
+    ];
+    for (args, error) in cases {
+        assert_eq!(args.validate(), Ok(()));
+        assert_eq!(opposite.preview_update(args, DAY).unwrap_err(), error);
+    }
+
+    // refused regardless of the Stream
+    let frequency_only = UpdateArgs {
+        withdraw_frequency: Some(3_600),
+        ..UpdateArgs::default()
+    };
+    assert_eq!(
+        frequency_only.validate(),
+        Err(UpdateError::WithdrawFrequencyWithoutAutomaticWithdrawal)
+    );
+    assert_eq!(
+        UpdateArgs::new().enable_automatic_withdrawal(0).validate(),
+        Err(UpdateError::ZeroWithdrawFrequency)
+    );
+    assert_eq!(
+        UpdateArgs::new().set_rate(0).validate(),
+        Err(UpdateError::ZeroAmountPerPeriod)
+    );
+}
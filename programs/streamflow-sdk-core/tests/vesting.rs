@@ -0,0 +1,75 @@
+mod common;
+
+use common::{fixture, recorded, DAY};
+use streamflow_sdk_core::state::Contract;
+
+/// Fixture paused 60 days after the cliff
+fn paused() -> (Contract, u64) {
+    let mut contract = recorded();
+    let pause_start = contract.ix.cliff + 60 * DAY;
+    contract.current_pause_start = pause_start;
+    (contract, pause_start)
+}
+
+#[test]
+fn queries_before_pause_start() {
+    let (contract, pause_start) = paused();
+    let reference = recorded();
+    let now = contract.ix.cliff + 10 * DAY;
+
+    assert_eq!(contract.pause_time(now), 0);
+    assert_eq!(contract.unlocked_at(now), reference.unlocked_at(now));
+    assert_eq!(contract.locked_at(now), reference.locked_at(now));
+    assert_eq!(contract.unvested_at(now), reference.unvested_at(now));
+    assert_eq!(contract.next_unlock(now), Some(now + DAY));
+    // the end of a paused Stream isn't known
+    assert_eq!(contract.lock_weight(now), 0);
+
+    // unlocks stop at the pause start
+    let unlocks: Vec<_> = contract.schedule(pause_start - 2 * DAY).collect();
+    assert_eq!(
+        unlocks.iter().map(|u| u.time).collect::<Vec<_>>(),
+        [pause_start - DAY, pause_start]
+    );
+    assert_eq!(unlocks[1].unlocked, reference.unlocked_at(pause_start));
+    assert_eq!(contract.next_unlock(pause_start), None);
+    assert_eq!(
+        contract.unlocked_at(pause_start + 30 * DAY),
+        reference.unlocked_at(pause_start)
+    );
+    assert_eq!(contract.pause_time(pause_start + 30 * DAY), 30 * DAY);
+}
+
+#[test]
+fn queries_before_start() {
+    let (contract, _) = paused();
+    let now = contract.ix.start_time - 1;
+    assert_eq!(contract.unlocked_at(now), 0);
+    assert_eq!(contract.locked_at(now), contract.ix.net_amount_deposited);
+    assert_eq!(contract.next_unlock(now), Some(contract.ix.cliff));
+
+    // paused before the cliff, nothing unlocks until unpaused
+    let mut contract = recorded();
+    contract.current_pause_start = contract.ix.start_time + DAY;
+    assert_eq!(contract.next_unlock(now), None);
+    assert_eq!(contract.unlocked_at(contract.end_time + DAY), 0);
+    assert_eq!(contract.schedule(now).count(), 0);
+}
+
+#[test]
+fn queries_before_effective_start() {
+    let contract = fixture();
+    let now = contract.ix.cliff + DAY;
+    assert!(now < contract.last_rate_change_time);
+
+    // history before the last rate change isn't recorded, only its total
+    let unlocked = contract.funds_unlocked_at_last_rate_change + contract.ix.cliff_amount;
+    assert_eq!(contract.unlocked_at(now), unlocked);
+    let next = contract.last_rate_change_time + contract.pause_cumulative + contract.ix.period;
+    assert_eq!(contract.next_unlock(now), Some(next));
+    assert_eq!(contract.unlocked_at(next - 1), unlocked);
+    assert_eq!(
+        contract.unlocked_at(next),
+        unlocked + contract.ix.amount_per_period
+    );
+}
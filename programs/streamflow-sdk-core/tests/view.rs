@@ -0,0 +1,74 @@
+mod common;
+
+use borsh::BorshSerialize;
+use common::{fixture, METADATA_V3};
+use streamflow_sdk_core::state::Contract;
+use streamflow_sdk_core::view::{ContractView, CONTRACT_LEN};
+
+fn assert_view_matches(data: &[u8], contract: &Contract) {
+    let view = ContractView::new(data).unwrap();
+    assert_eq!(view.magic(), contract.magic);
+    assert_eq!(view.version(), contract.version);
+    assert_eq!(view.created_at(), contract.created_at);
+    assert_eq!(view.amount_withdrawn(), contract.amount_withdrawn);
+    assert_eq!(view.canceled_at(), contract.canceled_at);
+    assert_eq!(view.end_time(), contract.end_time);
+    assert_eq!(view.last_withdrawn_at(), contract.last_withdrawn_at);
+    assert_eq!(view.sender(), contract.sender);
+    assert_eq!(view.sender_tokens(), contract.sender_tokens);
+    assert_eq!(view.recipient(), contract.recipient);
+    assert_eq!(view.recipient_tokens(), contract.recipient_tokens);
+    assert_eq!(view.mint(), contract.mint);
+    assert_eq!(view.escrow_tokens(), contract.escrow_tokens);
+    assert_eq!(view.streamflow_fee_total(), contract.streamflow_fee_total);
+    assert_eq!(view.partner_fee_total(), contract.partner_fee_total);
+    assert_eq!(
+        view.net_amount_deposited(),
+        contract.ix.net_amount_deposited
+    );
+    assert_eq!(view.closed(), contract.closed);
+    assert_eq!(view.vesting(), contract.vesting());
+}
+
+#[test]
+fn reads_fields_in_place() {
+    let contract = fixture();
+    assert_eq!(contract.try_to_vec().unwrap().len(), CONTRACT_LEN);
+    assert_view_matches(METADATA_V3, &contract);
+
+    // fields that are zero in the fixture
+    let mut changed = contract.clone();
+    changed.closed = true;
+    changed.canceled_at = 1_720_000_000;
+    changed.current_pause_start = 1_715_000_000;
+    changed.partner_fee_total = 42;
+    changed.last_withdrawn_at = 1_712_000_000;
+    assert_view_matches(&changed.try_to_vec().unwrap(), &changed);
+
+    assert!(ContractView::new(&METADATA_V3[..CONTRACT_LEN - 1]).is_none());
+}
+
+#[test]
+fn locked_balance() {
+    let contract = fixture();
+    let vesting = ContractView::new(METADATA_V3).unwrap().vesting();
+    let now = 1_710_000_000;
+
+    let unvested = contract.ix.net_amount_deposited - contract.unlocked_at(now);
+    assert_eq!(vesting.unvested_at(now), unvested);
+    assert_eq!(vesting.locked_at(now), unvested);
+    assert_eq!(vesting.locked_at(contract.end_time), 0);
+    assert_eq!(vesting.lock_weight(contract.end_time), 0);
+
+    let expected: u128 = contract
+        .schedule(now)
+        .map(|u| u128::from(u.amount) * u128::from(u.time - now))
+        .sum();
+    assert_eq!(vesting.lock_weight(now), expected);
+
+    // vesting stops at the cancel, the rest went back to the sender
+    let canceled = contract.preview_cancel(now).unwrap().contract;
+    assert_eq!(canceled.unvested_at(now + 86_400 * 30), unvested);
+    assert_eq!(canceled.locked_at(now), 0);
+    assert_eq!(canceled.lock_weight(now), 0);
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use common::DAY;
+use streamflow_sdk_core::state::{calculate_fee_from_amount, Contract};
+use streamflow_sdk_core::withdraw::WithdrawError;
+
+const START: u64 = 1_700_000_000;
+const CLIFF: u64 = START + 10 * DAY;
+const STREAMFLOW_FEE_PERCENT: f32 = 0.25;
+const PARTNER_FEE_PERCENT: f32 = 0.5;
+
+/// 100k tokens at the cliff, then 10k a day for 90 days, with treasury and partner fees
+fn stream() -> Contract {
+    let mut contract = common::contract(START, 1_000_000, DAY, 10_000, CLIFF, 100_000);
+    contract.streamflow_fee_percent = STREAMFLOW_FEE_PERCENT;
+    contract.streamflow_fee_total = calculate_fee_from_amount(1_000_000, STREAMFLOW_FEE_PERCENT);
+    contract.partner_fee_percent = PARTNER_FEE_PERCENT;
+    contract.partner_fee_total = calculate_fee_from_amount(1_000_000, PARTNER_FEE_PERCENT);
+    contract
+}
+
+/// Fee unlocked with the cliff and `vested` streamed tokens
+fn fee(vested: u64, percentage: f32) -> u64 {
+    calculate_fee_from_amount(vested, percentage) + calculate_fee_from_amount(100_000, percentage)
+}
+
+#[test]
+fn withdraws_everything_available() {
+    let contract = stream();
+    let now = CLIFF + 10 * DAY;
+    let preview = contract.preview_withdraw(u64::MAX, now).unwrap();
+    assert_eq!(preview.recipient_amount, 200_000);
+    assert_eq!(preview.streamflow_fee, fee(100_000, STREAMFLOW_FEE_PERCENT));
+    assert_eq!(preview.partner_fee, fee(100_000, PARTNER_FEE_PERCENT));
+
+    let after = &preview.contract;
+    assert_eq!(after.amount_withdrawn, 200_000);
+    assert_eq!(after.last_withdrawn_at, now);
+    assert_eq!(after.streamflow_fee_withdrawn, preview.streamflow_fee);
+    assert_eq!(after.partner_fee_withdrawn, preview.partner_fee);
+    // nothing is left until the next unlock
+    assert_eq!(
+        after.preview_withdraw(u64::MAX, now).unwrap_err(),
+        WithdrawError::ZeroAmount
+    );
+    assert_eq!(
+        after
+            .preview_withdraw(u64::MAX, now + DAY)
+            .unwrap()
+            .recipient_amount,
+        10_000
+    );
+
+    // before the cliff nothing is available
+    assert_eq!(
+        contract.preview_withdraw(u64::MAX, CLIFF - 1).unwrap_err(),
+        WithdrawError::ZeroAmount
+    );
+    // after the end the whole remaining deposit is
+    let end = contract
+        .preview_withdraw(u64::MAX, contract.end_time)
+        .unwrap();
+    assert_eq!(end.recipient_amount, 1_000_000);
+    assert_eq!(end.streamflow_fee, contract.streamflow_fee_total);
+    assert_eq!(end.partner_fee, contract.partner_fee_total);
+}
+
+#[test]
+fn rejects_amount_exceeding_available() {
+    let contract = stream();
+    let now = CLIFF + 10 * DAY;
+    assert_eq!(
+        contract.preview_withdraw(200_001, now).unwrap_err(),
+        WithdrawError::AmountExceedsAvailable {
+            requested: 200_001,
+            available: 200_000,
+        }
+    );
+    assert_eq!(
+        contract
+            .preview_withdraw(200_000, now)
+            .unwrap()
+            .recipient_amount,
+        200_000
+    );
+
+    // withdrawn tokens are no longer available
+    let after = contract.preview_withdraw(50_000, now).unwrap().contract;
+    assert_eq!(
+        after.preview_withdraw(150_001, now).unwrap_err(),
+        WithdrawError::AmountExceedsAvailable {
+            requested: 150_001,
+            available: 150_000,
+        }
+    );
+    assert_eq!(
+        contract.preview_withdraw(0, now).unwrap_err(),
+        WithdrawError::ZeroAmount
+    );
+
+    let mut closed = stream();
+    closed.closed = true;
+    assert_eq!(
+        closed.preview_withdraw(1, now).unwrap_err(),
+        WithdrawError::StreamClosed
+    );
+}
+
+#[test]
+fn splits_fees() {
+    let contract = stream();
+    let now = CLIFF + 10 * DAY;
+
+    // unlocked fees are withdrawn whole, regardless of the recipient amount
+    let first = contract.preview_withdraw(1, now).unwrap();
+    assert_eq!(first.recipient_amount, 1);
+    assert_eq!(first.streamflow_fee, fee(100_000, STREAMFLOW_FEE_PERCENT));
+    assert_eq!(first.partner_fee, fee(100_000, PARTNER_FEE_PERCENT));
+
+    // later withdrawals only take fees unlocked since
+    let later = now + 20 * DAY;
+    let second = first.contract.preview_withdraw(u64::MAX, later).unwrap();
+    assert_eq!(second.recipient_amount, 400_000 - 1);
+    assert_eq!(
+        second.streamflow_fee,
+        fee(300_000, STREAMFLOW_FEE_PERCENT) - first.streamflow_fee
+    );
+    assert_eq!(
+        second.partner_fee,
+        fee(300_000, PARTNER_FEE_PERCENT) - first.partner_fee
+    );
+
+    // fees withdrawn over the Stream add up to the totals
+    let last = second
+        .contract
+        .preview_withdraw(u64::MAX, contract.end_time)
+        .unwrap();
+    assert_eq!(
+        first.streamflow_fee + second.streamflow_fee + last.streamflow_fee,
+        contract.streamflow_fee_total
+    );
+    assert_eq!(
+        first.partner_fee + second.partner_fee + last.partner_fee,
+        contract.partner_fee_total
+    );
+    assert_eq!(last.contract.amount_withdrawn, 1_000_000);
+
+    // without a partner only the treasury takes a fee
+    let mut no_partner = stream();
+    no_partner.partner_fee_percent = 0.0;
+    no_partner.partner_fee_total = 0;
+    let preview = no_partner.preview_withdraw(u64::MAX, now).unwrap();
+    assert_eq!(preview.partner_fee, 0);
+    assert_eq!(preview.streamflow_fee, first.streamflow_fee);
+}
@@ -0,0 +1,353 @@
+//! Python bindings for decoding Streams and the vesting math of the Streamflow protocol.
+//!
+//! Built on `streamflow-sdk-core`, classes mirror `state::Contract` and `state::CreateParams`
+//! field by field, pubkeys are base58 strings and `stream_name` is a trimmed string.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use streamflow_sdk_core::schedule;
+use streamflow_sdk_core::state;
+use streamflow_sdk_core::versioned::VersionedContract;
+
+/// Parameters the Stream was created with
+#[pyclass(frozen, module = "streamflow_sdk_py")]
+#[derive(Clone, Debug)]
+pub struct CreateParams {
+    #[pyo3(get)]
+    pub start_time: u64,
+    #[pyo3(get)]
+    pub net_amount_deposited: u64,
+    #[pyo3(get)]
+    pub period: u64,
+    #[pyo3(get)]
+    pub amount_per_period: u64,
+    #[pyo3(get)]
+    pub cliff: u64,
+    #[pyo3(get)]
+    pub cliff_amount: u64,
+    #[pyo3(get)]
+    pub cancelable_by_sender: bool,
+    #[pyo3(get)]
+    pub cancelable_by_recipient: bool,
+    #[pyo3(get)]
+    pub automatic_withdrawal: bool,
+    #[pyo3(get)]
+    pub transferable_by_sender: bool,
+    #[pyo3(get)]
+    pub transferable_by_recipient: bool,
+    #[pyo3(get)]
+    pub can_topup: bool,
+    #[pyo3(get)]
+    pub stream_name: String,
+    #[pyo3(get)]
+    pub withdraw_frequency: u64,
+    #[pyo3(get)]
+    pub pausable: bool,
+    #[pyo3(get)]
+    pub can_update_rate: bool,
+    #[pyo3(get)]
+    pub is_pda: bool,
+    #[pyo3(get)]
+    pub nonce: u32,
+}
+
+impl From<&state::CreateParams> for CreateParams {
+    fn from(ix: &state::CreateParams) -> Self {
+        CreateParams {
+            start_time: ix.start_time,
+            net_amount_deposited: ix.net_amount_deposited,
+            period: ix.period,
+            amount_per_period: ix.amount_per_period,
+            cliff: ix.cliff,
+            cliff_amount: ix.cliff_amount,
+            cancelable_by_sender: ix.cancelable_by_sender,
+            cancelable_by_recipient: ix.cancelable_by_recipient,
+            automatic_withdrawal: ix.automatic_withdrawal,
+            transferable_by_sender: ix.transferable_by_sender,
+            transferable_by_recipient: ix.transferable_by_recipient,
+            can_topup: ix.can_topup,
+            stream_name: state::trim_stream_name(&ix.stream_name),
+            withdraw_frequency: ix.withdraw_frequency,
+            pausable: ix.pausable,
+            can_update_rate: ix.can_update_rate,
+            is_pda: ix.is_pda,
+            nonce: ix.nonce,
+        }
+    }
+}
+
+/// Tokens unlocked at `time`
+#[pyclass(frozen, get_all, module = "streamflow_sdk_py")]
+#[derive(Clone, Copy, Debug)]
+pub struct Unlock {
+    pub time: u64,
+    pub amount: u64,
+    /// Tokens unlocked in total after `time`, including withdrawn ones
+    pub unlocked: u64,
+}
+
+#[pymethods]
+impl Unlock {
+    fn __repr__(&self) -> String {
+        format!(
+            "Unlock(time={}, amount={}, unlocked={})",
+            self.time, self.amount, self.unlocked
+        )
+    }
+}
+
+impl From<schedule::Unlock> for Unlock {
+    fn from(unlock: schedule::Unlock) -> Self {
+        Unlock {
+            time: unlock.time,
+            amount: unlock.amount,
+            unlocked: unlock.unlocked,
+        }
+    }
+}
+
+/// Token transfers of a `withdraw` or `cancel`
+#[pyclass(frozen, get_all, module = "streamflow_sdk_py")]
+#[derive(Clone, Copy, Debug)]
+pub struct FeeQuote {
+    pub recipient_amount: u64,
+    /// Always 0 for withdrawals
+    pub sender_amount: u64,
+    pub streamflow_fee: u64,
+    pub partner_fee: u64,
+}
+
+#[pymethods]
+impl FeeQuote {
+    fn __repr__(&self) -> String {
+        format!(
+            "FeeQuote(recipient_amount={}, sender_amount={}, streamflow_fee={}, partner_fee={})",
+            self.recipient_amount, self.sender_amount, self.streamflow_fee, self.partner_fee
+        )
+    }
+}
+
+/// Stream metadata account, decoded with `Contract.decode`
+#[pyclass(frozen, module = "streamflow_sdk_py")]
+#[derive(Clone, Debug)]
+pub struct Contract {
+    inner: VersionedContract,
+    #[pyo3(get)]
+    pub magic: u64,
+    #[pyo3(get)]
+    pub version: u8,
+    #[pyo3(get)]
+    pub created_at: u64,
+    #[pyo3(get)]
+    pub amount_withdrawn: u64,
+    #[pyo3(get)]
+    pub canceled_at: u64,
+    #[pyo3(get)]
+    pub end_time: u64,
+    #[pyo3(get)]
+    pub last_withdrawn_at: u64,
+    #[pyo3(get)]
+    pub sender: String,
+    #[pyo3(get)]
+    pub sender_tokens: String,
+    #[pyo3(get)]
+    pub recipient: String,
+    #[pyo3(get)]
+    pub recipient_tokens: String,
+    #[pyo3(get)]
+    pub mint: String,
+    #[pyo3(get)]
+    pub escrow_tokens: String,
+    #[pyo3(get)]
+    pub streamflow_treasury: String,
+    #[pyo3(get)]
+    pub streamflow_treasury_tokens: String,
+    #[pyo3(get)]
+    pub streamflow_fee_total: u64,
+    #[pyo3(get)]
+    pub streamflow_fee_withdrawn: u64,
+    #[pyo3(get)]
+    pub streamflow_fee_percent: f32,
+    #[pyo3(get)]
+    pub partner: String,
+    #[pyo3(get)]
+    pub partner_tokens: String,
+    #[pyo3(get)]
+    pub partner_fee_total: u64,
+    #[pyo3(get)]
+    pub partner_fee_withdrawn: u64,
+    #[pyo3(get)]
+    pub partner_fee_percent: f32,
+    #[pyo3(get)]
+    pub ix: CreateParams,
+    #[pyo3(get)]
+    pub closed: bool,
+    #[pyo3(get)]
+    pub current_pause_start: u64,
+    #[pyo3(get)]
+    pub pause_cumulative: u64,
+    #[pyo3(get)]
+    pub last_rate_change_time: u64,
+    #[pyo3(get)]
+    pub funds_unlocked_at_last_rate_change: u64,
+    #[pyo3(get)]
+    pub creation_fee: u32,
+    #[pyo3(get)]
+    pub creation_fee_claimed: bool,
+    #[pyo3(get)]
+    pub auto_claim_fee: u32,
+    #[pyo3(get)]
+    pub auto_claim_fee_claimed: bool,
+    #[pyo3(get)]
+    pub old_metadata: String,
+    #[pyo3(get)]
+    pub payer: String,
+    #[pyo3(get)]
+    pub bump: u8,
+}
+
+impl From<VersionedContract> for Contract {
+    fn from(stream: VersionedContract) -> Self {
+        let c = &stream.contract;
+        Contract {
+            magic: c.magic,
+            version: c.version,
+            created_at: c.created_at,
+            amount_withdrawn: c.amount_withdrawn,
+            canceled_at: c.canceled_at,
+            end_time: c.end_time,
+            last_withdrawn_at: c.last_withdrawn_at,
+            sender: c.sender.to_string(),
+            sender_tokens: c.sender_tokens.to_string(),
+            recipient: c.recipient.to_string(),
+            recipient_tokens: c.recipient_tokens.to_string(),
+            mint: c.mint.to_string(),
+            escrow_tokens: c.escrow_tokens.to_string(),
+            streamflow_treasury: c.streamflow_treasury.to_string(),
+            streamflow_treasury_tokens: c.streamflow_treasury_tokens.to_string(),
+            streamflow_fee_total: c.streamflow_fee_total,
+            streamflow_fee_withdrawn: c.streamflow_fee_withdrawn,
+            streamflow_fee_percent: c.streamflow_fee_percent,
+            partner: c.partner.to_string(),
+            partner_tokens: c.partner_tokens.to_string(),
+            partner_fee_total: c.partner_fee_total,
+            partner_fee_withdrawn: c.partner_fee_withdrawn,
+            partner_fee_percent: c.partner_fee_percent,
+            ix: CreateParams::from(&c.ix),
+            closed: c.closed,
+            current_pause_start: c.current_pause_start,
+            pause_cumulative: c.pause_cumulative,
+            last_rate_change_time: c.last_rate_change_time,
+            funds_unlocked_at_last_rate_change: c.funds_unlocked_at_last_rate_change,
+            creation_fee: c.creation_fee,
+            creation_fee_claimed: c.creation_fee_claimed,
+            auto_claim_fee: c.auto_claim_fee,
+            auto_claim_fee_claimed: c.auto_claim_fee_claimed,
+            old_metadata: c.old_metadata.to_string(),
+            payer: c.payer.to_string(),
+            bump: c.bump,
+            inner: stream,
+        }
+    }
+}
+
+#[pymethods]
+impl Contract {
+    /// Decode metadata account data, raises `ValueError` if it isn't a Stream
+    #[staticmethod]
+    fn decode(data: &[u8]) -> PyResult<Self> {
+        let stream = VersionedContract::decode(data)
+            .map_err(|e| PyValueError::new_err(format!("invalid stream: {e}")))?;
+        Ok(stream.into())
+    }
+
+    /// Time unlocks begin, the cliff if set, otherwise `ix.start_time`
+    fn start_time(&self) -> u64 {
+        self.inner.contract.start_time()
+    }
+
+    #[pyo3(signature = (now, fee_percentage = 100.0))]
+    fn available_to_claim(&self, now: u64, fee_percentage: f32) -> u64 {
+        self.inner.contract.available_to_claim(now, fee_percentage)
+    }
+
+    /// Tokens unlocked by `now` in total, including withdrawn ones
+    fn unlocked_at(&self, now: u64) -> u64 {
+        self.inner.contract.unlocked_at(now)
+    }
+
+    fn next_unlock(&self, now: u64) -> Option<u64> {
+        self.inner.contract.next_unlock(now)
+    }
+
+    /// `scheduled`, `streaming`, `paused`, `completed` or `closed`
+    fn status(&self, now: u64) -> String {
+        self.inner.contract.status(now).to_string()
+    }
+
+    /// Unlocks after `now`, at most `limit` of them
+    #[pyo3(signature = (now, limit = None))]
+    fn schedule(&self, now: u64, limit: Option<usize>) -> Vec<Unlock> {
+        self.inner
+            .contract
+            .schedule(now)
+            .take(limit.unwrap_or(usize::MAX))
+            .map(Unlock::from)
+            .collect()
+    }
+
+    /// Transfers of a `withdraw` of `amount` at `now`, `None` withdraws everything available
+    #[pyo3(signature = (now, amount = None))]
+    fn preview_withdraw(&self, now: u64, amount: Option<u64>) -> PyResult<FeeQuote> {
+        let preview = self
+            .inner
+            .contract
+            .preview_withdraw(amount.unwrap_or(u64::MAX), now)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(FeeQuote {
+            recipient_amount: preview.recipient_amount,
+            sender_amount: 0,
+            streamflow_fee: preview.streamflow_fee,
+            partner_fee: preview.partner_fee,
+        })
+    }
+
+    /// Transfers of a `cancel` at `now`
+    fn preview_cancel(&self, now: u64) -> PyResult<FeeQuote> {
+        let preview = self
+            .inner
+            .contract
+            .preview_cancel(now)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(FeeQuote {
+            recipient_amount: preview.recipient_amount,
+            sender_amount: preview.sender_amount,
+            streamflow_fee: preview.streamflow_fee,
+            partner_fee: preview.partner_fee,
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Contract(name={:?}, mint={}, deposited={})",
+            self.ix.stream_name, self.mint, self.ix.net_amount_deposited
+        )
+    }
+}
+
+/// Fee of `percentage` taken from `amount`, rounded the same way as by the protocol
+#[pyfunction]
+fn calculate_fee_from_amount(amount: u64, percentage: f32) -> u64 {
+    state::calculate_fee_from_amount(amount, percentage)
+}
+
+#[pymodule]
+pub fn streamflow_sdk_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Contract>()?;
+    m.add_class::<CreateParams>()?;
+    m.add_class::<Unlock>()?;
+    m.add_class::<FeeQuote>()?;
+    m.add_function(wrap_pyfunction!(calculate_fee_from_amount, m)?)?;
+    m.add("METADATA_LEN", state::METADATA_LEN)?;
+    Ok(())
+}
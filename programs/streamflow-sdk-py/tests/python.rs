@@ -0,0 +1,66 @@
+use pyo3::ffi::c_str;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+const METADATA_V3: &[u8] = include_bytes!("../../streamflow-sdk/tests/fixtures/metadata_v3.bin");
+
+/// Run `code` with the module imported as `sf` and the v3 fixture bound to `data`
+fn run(code: &std::ffi::CStr) {
+    Python::with_gil(|py| {
+        let module = pyo3::wrap_pymodule!(streamflow_sdk_py::streamflow_sdk_py)(py);
+        let locals = PyDict::new(py);
+        locals.set_item("sf", module).unwrap();
+        locals
+            .set_item("data", PyBytes::new(py, METADATA_V3))
+            .unwrap();
+        if let Err(e) = py.run(code, None, Some(&locals)) {
+            e.display(py);
+            panic!("python code failed");
+        }
+    });
+}
+
+#[test]
+fn decodes_fields() {
+    run(c_str!(
+        r#"
+c = sf.Contract.decode(data)
+assert c.version == 3
+assert c.ix.stream_name == "Team vesting Q1"
+assert c.ix.net_amount_deposited == 1_000_000_000
+assert c.amount_withdrawn == 250_000_000
+assert c.ix.is_pda and c.ix.nonce == 7
+assert len(c.mint) >= 32
+assert c.status(1_700_000_000) == "scheduled"
+
+try:
+    sf.Contract.decode(data[:100])
+    raise AssertionError("decoded truncated data")
+except ValueError:
+    pass
+"#
+    ));
+}
+
+#[test]
+fn computes_unlocks_and_fees() {
+    run(c_str!(
+        r#"
+c = sf.Contract.decode(data)
+later = 1_710_000_000
+assert c.available_to_claim(later) == c.unlocked_at(later) - c.amount_withdrawn
+
+schedule = c.schedule(1_700_000_000)
+assert schedule[0].time == c.next_unlock(1_700_000_000) == 1_702_592_000
+assert schedule[-1].unlocked == c.ix.net_amount_deposited
+assert sum(u.amount for u in schedule) == c.ix.net_amount_deposited - c.unlocked_at(1_700_000_000)
+assert len(c.schedule(1_700_000_000, limit=3)) == 3
+
+withdraw = c.preview_withdraw(later)
+assert withdraw.recipient_amount == c.available_to_claim(later)
+cancel = c.preview_cancel(later)
+assert cancel.recipient_amount + cancel.sender_amount == c.ix.net_amount_deposited - c.amount_withdrawn
+assert sf.calculate_fee_from_amount(1_000_000, 0.25) == 2_500
+"#
+    ));
+}
@@ -0,0 +1,264 @@
+//! WebAssembly bindings for decoding Streams and the vesting math of the Streamflow protocol.
+//!
+//! Built on `streamflow-sdk-core`, amounts and timestamps are `u64` and map to `BigInt` in JS,
+//! pubkeys are base58 strings.
+
+use solana_pubkey::Pubkey;
+use streamflow_sdk_core::{schedule, state, timeline, versioned::VersionedContract};
+use wasm_bindgen::prelude::*;
+
+/// Cluster the Streamflow program is deployed to
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cluster {
+    Mainnet,
+    Devnet,
+}
+
+impl From<Cluster> for state::Cluster {
+    fn from(cluster: Cluster) -> Self {
+        match cluster {
+            Cluster::Mainnet => state::Cluster::Mainnet,
+            Cluster::Devnet => state::Cluster::Devnet,
+        }
+    }
+}
+
+/// Lifecycle stage of a Stream, see `StreamStatus` of `streamflow-sdk-core`
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamStatus {
+    Scheduled,
+    Streaming,
+    Paused,
+    Completed,
+    Closed,
+}
+
+impl From<timeline::StreamStatus> for StreamStatus {
+    fn from(status: timeline::StreamStatus) -> Self {
+        match status {
+            timeline::StreamStatus::Scheduled => StreamStatus::Scheduled,
+            timeline::StreamStatus::Streaming => StreamStatus::Streaming,
+            timeline::StreamStatus::Paused => StreamStatus::Paused,
+            timeline::StreamStatus::Completed => StreamStatus::Completed,
+            timeline::StreamStatus::Closed => StreamStatus::Closed,
+        }
+    }
+}
+
+/// Tokens unlocked at `time`
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Unlock {
+    pub time: u64,
+    pub amount: u64,
+    /// Tokens unlocked in total after `time`, including withdrawn ones
+    pub unlocked: u64,
+}
+
+impl From<schedule::Unlock> for Unlock {
+    fn from(unlock: schedule::Unlock) -> Self {
+        Unlock {
+            time: unlock.time,
+            amount: unlock.amount,
+            unlocked: unlock.unlocked,
+        }
+    }
+}
+
+/// Token transfers of a `withdraw` or `cancel`
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeQuote {
+    /// Tokens sent to the recipient
+    #[wasm_bindgen(js_name = recipientAmount)]
+    pub recipient_amount: u64,
+    /// Tokens returned to the sender, always 0 for withdrawals
+    #[wasm_bindgen(js_name = senderAmount)]
+    pub sender_amount: u64,
+    /// Tokens sent to the Streamflow treasury
+    #[wasm_bindgen(js_name = streamflowFee)]
+    pub streamflow_fee: u64,
+    /// Tokens sent to the partner
+    #[wasm_bindgen(js_name = partnerFee)]
+    pub partner_fee: u64,
+}
+
+/// Stream metadata account decoded according to its version
+#[wasm_bindgen]
+pub struct Stream {
+    inner: VersionedContract,
+}
+
+#[wasm_bindgen]
+impl Stream {
+    /// Decode metadata account data
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(data: &[u8]) -> Result<Stream, JsError> {
+        let inner = VersionedContract::decode(data)
+            .map_err(|e| JsError::new(&format!("invalid stream: {e}")))?;
+        Ok(Stream { inner })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn version(&self) -> u8 {
+        self.inner.contract.version
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        state::trim_stream_name(&self.inner.contract.ix.stream_name)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sender(&self) -> String {
+        self.inner.contract.sender.to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn recipient(&self) -> String {
+        self.inner.contract.recipient.to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn mint(&self) -> String {
+        self.inner.contract.mint.to_string()
+    }
+
+    #[wasm_bindgen(getter, js_name = escrowTokens)]
+    pub fn escrow_tokens(&self) -> String {
+        self.inner.contract.escrow_tokens.to_string()
+    }
+
+    #[wasm_bindgen(getter, js_name = startTime)]
+    pub fn start_time(&self) -> u64 {
+        self.inner.contract.start_time()
+    }
+
+    #[wasm_bindgen(getter, js_name = endTime)]
+    pub fn end_time(&self) -> u64 {
+        self.inner.contract.end_time
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn deposited(&self) -> u64 {
+        self.inner.contract.ix.net_amount_deposited
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn withdrawn(&self) -> u64 {
+        self.inner.contract.amount_withdrawn
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn closed(&self) -> bool {
+        self.inner.contract.closed
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn paused(&self) -> bool {
+        self.inner.contract.current_pause_start != 0
+    }
+
+    /// Nonce of the metadata PDA, `undefined` unless the Stream is a PDA
+    #[wasm_bindgen(getter)]
+    pub fn nonce(&self) -> Option<u32> {
+        self.inner.nonce()
+    }
+
+    /// Tokens the recipient can withdraw at `now`
+    #[wasm_bindgen(js_name = availableToClaim)]
+    pub fn available_to_claim(&self, now: u64) -> u64 {
+        self.inner.contract.available_to_claim(now, 100.0)
+    }
+
+    /// Tokens unlocked by `now` in total, including withdrawn ones
+    #[wasm_bindgen(js_name = unlockedAt)]
+    pub fn unlocked_at(&self, now: u64) -> u64 {
+        self.inner.contract.unlocked_at(now)
+    }
+
+    #[wasm_bindgen(js_name = nextUnlock)]
+    pub fn next_unlock(&self, now: u64) -> Option<u64> {
+        self.inner.contract.next_unlock(now)
+    }
+
+    pub fn status(&self, now: u64) -> StreamStatus {
+        self.inner.contract.status(now).into()
+    }
+
+    /// Up to `limit` unlocks after `now`
+    pub fn schedule(&self, now: u64, limit: usize) -> Vec<Unlock> {
+        self.inner
+            .contract
+            .schedule(now)
+            .take(limit)
+            .map(Unlock::from)
+            .collect()
+    }
+
+    /// Transfers of a `withdraw` of `amount` at `now`, `u64::MAX` withdraws everything available
+    #[wasm_bindgen(js_name = previewWithdraw)]
+    pub fn preview_withdraw(&self, amount: u64, now: u64) -> Result<FeeQuote, JsError> {
+        let preview = self
+            .inner
+            .contract
+            .preview_withdraw(amount, now)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(FeeQuote {
+            recipient_amount: preview.recipient_amount,
+            sender_amount: 0,
+            streamflow_fee: preview.streamflow_fee,
+            partner_fee: preview.partner_fee,
+        })
+    }
+
+    /// Transfers of a `cancel` at `now`
+    #[wasm_bindgen(js_name = previewCancel)]
+    pub fn preview_cancel(&self, now: u64) -> Result<FeeQuote, JsError> {
+        let preview = self
+            .inner
+            .contract
+            .preview_cancel(now)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        Ok(FeeQuote {
+            recipient_amount: preview.recipient_amount,
+            sender_amount: preview.sender_amount,
+            streamflow_fee: preview.streamflow_fee,
+            partner_fee: preview.partner_fee,
+        })
+    }
+}
+
+/// Fee of `percentage` taken from `amount`, rounded the same way as by the protocol
+#[wasm_bindgen(js_name = feeFromAmount)]
+pub fn fee_from_amount(amount: u64, percentage: f32) -> u64 {
+    state::calculate_fee_from_amount(amount, percentage)
+}
+
+/// Metadata PDA of a `create_v2` Stream
+#[wasm_bindgen(js_name = deriveMetadata)]
+pub fn derive_metadata(
+    mint: &str,
+    payer: &str,
+    nonce: u32,
+    cluster: Cluster,
+) -> Result<String, JsError> {
+    let program_id = state::Cluster::from(cluster).program_id();
+    let (metadata, _) = state::derive_metadata(&pubkey(mint)?, &pubkey(payer)?, nonce, &program_id);
+    Ok(metadata.to_string())
+}
+
+/// Escrow token account of the Stream stored at `metadata`
+#[wasm_bindgen(js_name = deriveEscrow)]
+pub fn derive_escrow(metadata: &str, cluster: Cluster) -> Result<String, JsError> {
+    let program_id = state::Cluster::from(cluster).program_id();
+    let (escrow, _) = state::find_escrow_account(pubkey(metadata)?.as_ref(), &program_id);
+    Ok(escrow.to_string())
+}
+
+fn pubkey(key: &str) -> Result<Pubkey, JsError> {
+    key.parse()
+        .map_err(|_| JsError::new(&format!("invalid pubkey {key}")))
+}
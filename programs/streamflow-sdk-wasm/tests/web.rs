@@ -0,0 +1,119 @@
+//! Run with `wasm-pack test --node`
+#![cfg(target_arch = "wasm32")]
+
+use streamflow_sdk_core::{state, view};
+use streamflow_sdk_wasm::{
+    derive_escrow, derive_metadata, fee_from_amount, Cluster, Stream, StreamStatus,
+};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const METADATA_V3: &[u8] = include_bytes!("../../streamflow-sdk/tests/fixtures/metadata_v3.bin");
+const NOW: u64 = 1_700_000_000;
+/// After the last rate change of the fixture
+const LATER: u64 = 1_710_000_000;
+const DAY: u64 = 86_400;
+
+#[wasm_bindgen_test]
+fn decodes_stream() {
+    let stream = Stream::from_bytes(METADATA_V3).unwrap();
+    assert_eq!(stream.version(), 3);
+    assert_eq!(stream.name(), "Team vesting Q1");
+    assert_eq!(stream.deposited(), 1_000_000_000);
+    assert_eq!(stream.withdrawn(), 250_000_000);
+    assert!(!stream.closed());
+    assert_eq!(stream.status(NOW), StreamStatus::Scheduled);
+    assert_eq!(stream.status(LATER), StreamStatus::Streaming);
+
+    assert!(Stream::from_bytes(&METADATA_V3[..100]).is_err());
+}
+
+#[wasm_bindgen_test]
+fn schedule_follows_unlocks() {
+    let stream = Stream::from_bytes(METADATA_V3).unwrap();
+    let schedule = stream.schedule(NOW, 3);
+    assert_eq!(schedule[0].time, stream.next_unlock(NOW).unwrap());
+    assert_eq!(schedule[0].time, 1_702_592_000);
+    for unlock in &schedule {
+        assert_eq!(stream.unlocked_at(unlock.time), unlock.unlocked);
+    }
+    assert_eq!(
+        stream.available_to_claim(LATER),
+        stream.unlocked_at(LATER) - stream.withdrawn()
+    );
+
+    let all = stream.schedule(NOW, usize::MAX);
+    assert_eq!(all.last().unwrap().unlocked, stream.deposited());
+}
+
+#[wasm_bindgen_test]
+fn name_matches_core() {
+    /// `CreateParams::stream_name` follows the eight amounts and six flags
+    const STREAM_NAME: usize = view::offset::IX + 54;
+    let mut data = METADATA_V3.to_vec();
+    data[STREAM_NAME..STREAM_NAME + 64].fill(0);
+    data[STREAM_NAME..STREAM_NAME + 4].copy_from_slice(b"a\0b\0");
+    let stream = Stream::from_bytes(&data).unwrap();
+    let name: [u8; 64] = data[STREAM_NAME..STREAM_NAME + 64].try_into().unwrap();
+    assert_eq!(stream.name(), state::trim_stream_name(&name));
+    assert_eq!(stream.name(), "a\0b");
+}
+
+#[wasm_bindgen_test]
+fn schedule_stops_at_pause() {
+    let pause_start = LATER + 30 * DAY;
+    let mut data = METADATA_V3.to_vec();
+    let offset = view::offset::CURRENT_PAUSE_START;
+    data[offset..offset + 8].copy_from_slice(&pause_start.to_le_bytes());
+    let stream = Stream::from_bytes(&data).unwrap();
+    assert!(stream.paused());
+    assert_eq!(stream.status(LATER), StreamStatus::Paused);
+
+    let schedule = stream.schedule(LATER, usize::MAX);
+    assert!(schedule.last().unwrap().time <= pause_start);
+    assert_eq!(
+        schedule.last().unwrap().unlocked,
+        stream.unlocked_at(pause_start + 30 * DAY)
+    );
+    assert!(stream.unlocked_at(pause_start) < stream.deposited());
+    assert_eq!(stream.next_unlock(pause_start), None);
+    assert!(stream.schedule(pause_start, usize::MAX).is_empty());
+}
+
+#[wasm_bindgen_test]
+fn quotes_fees() {
+    let stream = Stream::from_bytes(METADATA_V3).unwrap();
+    let available = stream.available_to_claim(LATER);
+    let withdraw = stream.preview_withdraw(u64::MAX, LATER).unwrap();
+    assert_eq!(withdraw.recipient_amount, available);
+    assert_eq!(withdraw.sender_amount, 0);
+
+    let cancel = stream.preview_cancel(LATER).unwrap();
+    assert_eq!(
+        cancel.recipient_amount + cancel.sender_amount,
+        stream.deposited() - stream.withdrawn()
+    );
+    assert_eq!(fee_from_amount(1_000_000, 0.25), 2_500);
+}
+
+#[wasm_bindgen_test]
+fn derives_addresses() {
+    let stream = Stream::from_bytes(METADATA_V3).unwrap();
+    let program_id = state::Cluster::Mainnet.program_id();
+
+    let mint = stream.mint();
+    let payer = state::STRM_TREASURY;
+    let metadata = derive_metadata(&mint, payer, 7, Cluster::Mainnet).unwrap();
+    let (expected, _) = state::derive_metadata(
+        &mint.parse().unwrap(),
+        &payer.parse().unwrap(),
+        7,
+        &program_id,
+    );
+    assert_eq!(metadata, expected.to_string());
+
+    let escrow = derive_escrow(&metadata, Cluster::Mainnet).unwrap();
+    let (expected, _) = state::find_escrow_account(expected.as_ref(), &program_id);
+    assert_eq!(escrow, expected.to_string());
+
+    assert!(derive_escrow("not a pubkey", Cluster::Devnet).is_err());
+}
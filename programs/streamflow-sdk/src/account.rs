@@ -0,0 +1,77 @@
+//! Typed Stream metadata account for Anchor `Accounts` structs
+
+use std::ops::Deref;
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+
+use crate::state::Contract;
+
+/// Stream metadata account, checks owner and magic on deserialization.
+///
+/// `Contract` has no discriminator, so the wrapper implements the Anchor account traits by hand.
+/// Fields of the Stream are available through `Deref`, so they can be used in constraints:
+///
+/// ```
+/// # use anchor_lang::prelude::*;
+/// # use streamflow_sdk::account::StreamMetadata;
+/// #[derive(Accounts)]
+/// pub struct Withdraw<'info> {
+///     #[account(mut, has_one = recipient, has_one = mint)]
+///     pub metadata: Box<Account<'info, StreamMetadata>>,
+///     // ...
+/// #   pub recipient: Signer<'info>,
+/// #   /// CHECK: compared with the Stream
+/// #   pub mint: UncheckedAccount<'info>,
+/// }
+/// # fn main() {}
+/// ```
+///
+/// The account is never written back, it's owned by the Streamflow program.
+#[derive(Clone, Debug)]
+pub struct StreamMetadata(pub Contract);
+
+impl StreamMetadata {
+    pub fn into_inner(self) -> Contract {
+        self.0
+    }
+}
+
+impl AccountDeserialize for StreamMetadata {
+    /// Deserialize a Stream, rejecting accounts without magic (allocated but not yet created)
+    fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+        let metadata = Self::try_deserialize_unchecked(buf)?;
+        if metadata.magic == 0 {
+            return err!(ErrorCode::AccountNotInitialized);
+        }
+        Ok(metadata)
+    }
+
+    fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+        Contract::deserialize(buf)
+            .map(Self)
+            .map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+    }
+}
+
+impl AccountSerialize for StreamMetadata {}
+
+impl Owner for StreamMetadata {
+    fn owner() -> Pubkey {
+        crate::id()
+    }
+}
+
+impl Deref for StreamMetadata {
+    type Target = Contract;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Contract> for StreamMetadata {
+    fn from(contract: Contract) -> Self {
+        Self(contract)
+    }
+}
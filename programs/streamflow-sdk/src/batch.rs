@@ -0,0 +1,419 @@
+//! Planning creation of many Streams at once, e.g. for payroll or airdrops
+
+use std::fmt;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program_pack::Pack;
+use solana_message::compiled_instruction::CompiledInstruction;
+use solana_message::Message;
+
+use crate::compute_budget::{set_compute_unit_limit, set_compute_unit_price, MAX_COMPUTE_UNITS};
+use crate::instructions;
+use crate::nonce::{find_free_nonce, AllocatedNonce, DEFAULT_NONCE_ATTEMPTS};
+use crate::state::{
+    calculate_fee_from_amount, find_escrow_account, CreateParams, METADATA_LEN, STRM_TREASURY,
+};
+
+/// Max size of a serialized transaction
+pub const MAX_TRANSACTION_SIZE: usize = 1232;
+/// Lamports paid per transaction signature
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5000;
+/// Default percentage of tokens deposited taken by Streamflow treasury
+pub const DEFAULT_STREAMFLOW_FEE_PERCENT: f32 = 0.25;
+
+/// Vesting schedule of a single Stream
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StreamSchedule {
+    pub start_time: u64,
+    pub period: u64,
+    pub amount_per_period: u64,
+    pub cliff: u64,
+    pub cliff_amount: u64,
+}
+
+/// Single Stream to create
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BatchEntry {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::pubkey"))]
+    pub recipient: Pubkey,
+    /// Amount of tokens to deposit
+    pub amount: u64,
+    pub schedule: StreamSchedule,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_helpers::stream_name"))]
+    pub stream_name: [u8; 64],
+}
+
+/// Settings shared by all Streams in a batch
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StreamOptions {
+    pub cancelable_by_sender: bool,
+    pub cancelable_by_recipient: bool,
+    pub automatic_withdrawal: bool,
+    pub transferable_by_sender: bool,
+    pub transferable_by_recipient: bool,
+    pub can_topup: bool,
+    pub withdraw_frequency: u64,
+    pub pausable: bool,
+    pub can_update_rate: bool,
+}
+
+/// Reasons why a batch can't be planned
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchError {
+    /// Entry at index has no tokens to deposit
+    ZeroAmount(usize),
+    /// Entry at index has zero `period` or `amount_per_period`, or `cliff_amount` above `amount`
+    InvalidSchedule(usize),
+    /// Nonces ran out of `u32` range
+    NonceOverflow,
+    /// No free nonce for the entry at index within `BatchPlanner::max_nonce_attempts`
+    NoFreeNonce(usize),
+    /// Single create instruction doesn't fit into a transaction with the configured limits
+    InstructionTooLarge,
+    /// Totals of the batch don't fit into `u64`
+    Overflow,
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchError::ZeroAmount(i) => write!(f, "entry {i} has zero amount"),
+            BatchError::InvalidSchedule(i) => write!(f, "entry {i} has invalid schedule"),
+            BatchError::NonceOverflow => f.write_str("nonce overflow"),
+            BatchError::NoFreeNonce(i) => write!(f, "no free nonce for entry {i}"),
+            BatchError::InstructionTooLarge => {
+                f.write_str("create instruction doesn't fit into a transaction")
+            }
+            BatchError::Overflow => f.write_str("batch totals overflow"),
+        }
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+/// Stream created by a planned transaction
+#[derive(Clone, Debug)]
+pub struct PlannedStream {
+    /// Index of the entry in the planned batch
+    pub index: usize,
+    pub recipient: Pubkey,
+    pub nonce: u32,
+    pub metadata: Pubkey,
+    pub escrow_tokens: Pubkey,
+}
+
+/// Group of create instructions fitting into a single transaction
+#[derive(Clone, Debug)]
+pub struct PlannedTransaction {
+    /// Compute budget instructions followed by the create instructions
+    pub instructions: Vec<Instruction>,
+    pub streams: Vec<PlannedStream>,
+    /// Size of the serialized transaction
+    pub size: usize,
+    /// Compute units budgeted for the transaction
+    pub compute_units: u32,
+}
+
+/// Result of `BatchPlanner::plan`
+#[derive(Clone, Debug)]
+pub struct BatchPlan {
+    pub transactions: Vec<PlannedTransaction>,
+    /// Tokens deposited to the Streams
+    pub total_deposited: u64,
+    /// Tokens taken by Streamflow treasury on creation
+    pub total_fees: u64,
+    /// Lamports needed for rent of metadata and escrow accounts
+    pub rent_lamports: u64,
+    /// Lamports charged by the protocol on creation
+    pub creation_fee_lamports: u64,
+    /// Lamports needed for transaction signatures
+    pub signature_lamports: u64,
+    /// Next nonce that hasn't been used by the batch
+    pub next_nonce: u32,
+}
+
+impl BatchPlan {
+    /// Tokens `sender` needs to hold to execute the batch, planning checks it fits into `u64`
+    pub fn total_tokens(&self) -> u64 {
+        self.total_deposited + self.total_fees
+    }
+
+    /// Lamports `sender` needs to hold to execute the batch, planning checks it fits into `u64`
+    pub fn total_lamports(&self) -> u64 {
+        self.rent_lamports + self.creation_fee_lamports + self.signature_lamports
+    }
+}
+
+/// Planner of `create_unchecked_v2` transactions for a list of entries
+#[derive(Clone, Debug)]
+pub struct BatchPlanner {
+    /// Wallet funding the Streams, signs and pays for every transaction
+    pub sender: Pubkey,
+    pub mint: Pubkey,
+    /// Partner to attribute the Streams to, Streamflow treasury by default
+    pub partner: Pubkey,
+    pub options: StreamOptions,
+    /// Nonce of the first Stream, following Streams use consecutive nonces
+    pub first_nonce: u32,
+    /// Nonces checked for every Stream by `plan_skipping`
+    pub max_nonce_attempts: u32,
+    pub streamflow_fee_percent: f32,
+    /// SOL fee the protocol charges per Stream
+    pub creation_fee_lamports: u64,
+    pub max_transaction_size: usize,
+    pub max_compute_units: u32,
+    /// Compute units budgeted for every `create_unchecked_v2`
+    pub compute_units_per_create: u32,
+    /// Priority fee of every transaction in micro-lamports per compute unit
+    pub compute_unit_price: Option<u64>,
+    /// Rent of the cluster, determines lamports needed for the created accounts
+    pub rent: Rent,
+}
+
+impl BatchPlanner {
+    /// Planner with default limits, take `rent` from the Rent sysvar of the cluster.
+    ///
+    /// Measure `compute_units_per_create` by simulating a `create_unchecked_v2` on the cluster.
+    pub fn new(
+        sender: Pubkey,
+        mint: Pubkey,
+        options: StreamOptions,
+        rent: Rent,
+        compute_units_per_create: u32,
+    ) -> Self {
+        Self {
+            sender,
+            mint,
+            partner: STRM_TREASURY.parse().unwrap(),
+            options,
+            first_nonce: 0,
+            max_nonce_attempts: DEFAULT_NONCE_ATTEMPTS,
+            streamflow_fee_percent: DEFAULT_STREAMFLOW_FEE_PERCENT,
+            creation_fee_lamports: 0,
+            max_transaction_size: MAX_TRANSACTION_SIZE,
+            max_compute_units: MAX_COMPUTE_UNITS,
+            compute_units_per_create,
+            compute_unit_price: None,
+            rent,
+        }
+    }
+
+    fn params(&self, entry: &BatchEntry, nonce: u32) -> CreateParams {
+        CreateParams {
+            start_time: entry.schedule.start_time,
+            net_amount_deposited: entry.amount,
+            period: entry.schedule.period,
+            amount_per_period: entry.schedule.amount_per_period,
+            cliff: entry.schedule.cliff,
+            cliff_amount: entry.schedule.cliff_amount,
+            cancelable_by_sender: self.options.cancelable_by_sender,
+            cancelable_by_recipient: self.options.cancelable_by_recipient,
+            automatic_withdrawal: self.options.automatic_withdrawal,
+            transferable_by_sender: self.options.transferable_by_sender,
+            transferable_by_recipient: self.options.transferable_by_recipient,
+            can_topup: self.options.can_topup,
+            stream_name: entry.stream_name,
+            withdraw_frequency: self.options.withdraw_frequency,
+            ghost: 0,
+            pausable: self.options.pausable,
+            can_update_rate: self.options.can_update_rate,
+            ghost2: 0,
+            is_pda: true,
+            nonce,
+        }
+    }
+
+    fn validate(entries: &[BatchEntry]) -> std::result::Result<(), BatchError> {
+        for (i, entry) in entries.iter().enumerate() {
+            if entry.amount == 0 {
+                return Err(BatchError::ZeroAmount(i));
+            }
+            let schedule = &entry.schedule;
+            if schedule.period == 0
+                || schedule.amount_per_period == 0
+                || schedule.cliff_amount > entry.amount
+            {
+                return Err(BatchError::InvalidSchedule(i));
+            }
+        }
+        Ok(())
+    }
+
+    /// Assign consecutive nonces to `entries` and group their create instructions into transactions
+    pub fn plan(&self, entries: &[BatchEntry]) -> std::result::Result<BatchPlan, BatchError> {
+        self.plan_skipping(entries, |_| false)
+    }
+
+    /// Same as `plan`, but skips nonces whose metadata accounts are `in_use`
+    pub fn plan_skipping(
+        &self,
+        entries: &[BatchEntry],
+        mut in_use: impl FnMut(&Pubkey) -> bool,
+    ) -> std::result::Result<BatchPlan, BatchError> {
+        Self::validate(entries)?;
+        let pid = crate::id();
+        let rent_per_stream = self.rent.minimum_balance(METADATA_LEN)
+            + self
+                .rent
+                .minimum_balance(anchor_spl::token::spl_token::state::Account::LEN);
+
+        let mut plan = BatchPlan {
+            transactions: Vec::new(),
+            total_deposited: 0,
+            total_fees: 0,
+            rent_lamports: 0,
+            creation_fee_lamports: 0,
+            signature_lamports: 0,
+            next_nonce: self.first_nonce,
+        };
+        let mut current: Option<PlannedTransaction> = None;
+        for (index, entry) in entries.iter().enumerate() {
+            let AllocatedNonce {
+                nonce, metadata, ..
+            } = find_free_nonce(
+                &self.mint,
+                &self.sender,
+                &pid,
+                plan.next_nonce,
+                self.max_nonce_attempts,
+                &mut in_use,
+            )
+            .map_err(|_| BatchError::NoFreeNonce(index))?;
+            plan.next_nonce = nonce.checked_add(1).ok_or(BatchError::NonceOverflow)?;
+            let params = self.params(entry, nonce);
+            let ix = instructions::create_unchecked_v2(
+                self.sender,
+                self.mint,
+                entry.recipient,
+                self.partner,
+                &params,
+            );
+            let (escrow_tokens, _) = find_escrow_account(metadata.as_ref(), &pid);
+            let stream = PlannedStream {
+                index,
+                recipient: entry.recipient,
+                nonce,
+                metadata,
+                escrow_tokens,
+            };
+
+            let fee = calculate_fee_from_amount(entry.amount, self.streamflow_fee_percent);
+            plan.total_deposited = checked_add(plan.total_deposited, entry.amount)?;
+            plan.total_fees = checked_add(plan.total_fees, fee)?;
+            plan.rent_lamports = checked_add(plan.rent_lamports, rent_per_stream)?;
+            plan.creation_fee_lamports =
+                checked_add(plan.creation_fee_lamports, self.creation_fee_lamports)?;
+
+            if let Some(tx) = current.as_mut() {
+                if self.try_push(tx, &ix) {
+                    tx.streams.push(stream);
+                    continue;
+                }
+            }
+            let mut tx = PlannedTransaction {
+                instructions: Vec::new(),
+                streams: vec![stream],
+                size: 0,
+                compute_units: 0,
+            };
+            if !self.try_push(&mut tx, &ix) {
+                return Err(BatchError::InstructionTooLarge);
+            }
+            plan.transactions.extend(current.replace(tx));
+        }
+        plan.transactions.extend(current);
+        plan.signature_lamports = plan
+            .transactions
+            .iter()
+            .map(|tx| {
+                let message = Message::new(&tx.instructions, Some(&self.sender));
+                message.header.num_required_signatures as u64 * LAMPORTS_PER_SIGNATURE
+            })
+            .sum();
+        checked_add(plan.total_deposited, plan.total_fees)?;
+        checked_add(plan.rent_lamports, plan.creation_fee_lamports)
+            .and_then(|lamports| checked_add(lamports, plan.signature_lamports))?;
+        Ok(plan)
+    }
+
+    /// Compute budget instructions of a transaction requesting `units`
+    fn compute_budget(&self, units: u32) -> Vec<Instruction> {
+        let mut instructions = vec![set_compute_unit_limit(units)];
+        instructions.extend(self.compute_unit_price.map(set_compute_unit_price));
+        instructions
+    }
+
+    /// Add `ix` to `tx` if it still fits into the limits, the compute budget covers all of them
+    fn try_push(&self, tx: &mut PlannedTransaction, ix: &Instruction) -> bool {
+        let Some(compute_units) = tx
+            .compute_units
+            .checked_add(self.compute_units_per_create)
+            .filter(|units| *units <= self.max_compute_units)
+        else {
+            return false;
+        };
+        let mut instructions = self.compute_budget(compute_units);
+        let creates = instructions.len().min(tx.instructions.len());
+        instructions.extend_from_slice(&tx.instructions[creates..]);
+        instructions.push(ix.clone());
+        let size = transaction_size(&Message::new(&instructions, Some(&self.sender)));
+        if size > self.max_transaction_size {
+            return false;
+        }
+        tx.instructions = instructions;
+        tx.size = size;
+        tx.compute_units = compute_units;
+        true
+    }
+}
+
+fn checked_add(a: u64, b: u64) -> std::result::Result<u64, BatchError> {
+    a.checked_add(b).ok_or(BatchError::Overflow)
+}
+
+pub(crate) fn compact_len(len: usize) -> usize {
+    match len {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
+}
+
+/// Size of a signed legacy transaction carrying `message`
+pub fn transaction_size(message: &Message) -> usize {
+    signed_message_size(
+        message.header.num_required_signatures as usize,
+        message.account_keys.len(),
+        &message.instructions,
+    )
+}
+
+/// Size of signatures, header, static keys, blockhash and instructions, the parts legacy and v0
+/// messages share
+pub(crate) fn signed_message_size(
+    signatures: usize,
+    keys: usize,
+    instructions: &[CompiledInstruction],
+) -> usize {
+    let instructions_len: usize = instructions
+        .iter()
+        .map(|ix| {
+            1 + compact_len(ix.accounts.len())
+                + ix.accounts.len()
+                + compact_len(ix.data.len())
+                + ix.data.len()
+        })
+        .sum();
+    compact_len(signatures)
+        + signatures * 64
+        + 3
+        + compact_len(keys)
+        + keys * 32
+        + 32
+        + compact_len(instructions.len())
+        + instructions_len
+}
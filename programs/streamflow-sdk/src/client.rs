@@ -0,0 +1,441 @@
+//! Async client reading Streams and sending Streamflow transactions
+//!
+//! RPC access goes through `StreamflowRpc`, `rpc::HttpRpc` implements it over the JSON-RPC API of
+//! a node, implement it on top of another RPC client, an in-process validator or a mock.
+
+use std::fmt;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use async_trait::async_trait;
+use solana_hash::Hash;
+use solana_signature::Signature;
+use solana_signer::{Signer, SignerError};
+use solana_system_interface::instruction::create_account;
+use solana_transaction::Transaction;
+
+pub use solana_account::Account;
+
+use crate::constraints::{load_contract_data, AccountError};
+use crate::instructions;
+use crate::state::{derive_metadata, Contract, CreateParams, METADATA_LEN};
+use crate::update::{update_instruction, UpdateArgs, UpdateError};
+use crate::view::offset;
+
+/// Error returned by an RPC implementation
+pub type RpcError = Box<dyn std::error::Error + Send + Sync>;
+
+/// `getProgramAccounts` filter comparing account data at `offset` with `bytes`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemcmpFilter {
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+}
+
+impl MemcmpFilter {
+    pub fn pubkey(offset: usize, key: &Pubkey) -> Self {
+        Self {
+            offset,
+            bytes: key.to_bytes().to_vec(),
+        }
+    }
+}
+
+/// RPC methods used by `StreamflowClient`
+#[async_trait]
+pub trait StreamflowRpc: Send + Sync {
+    async fn get_account(&self, key: &Pubkey) -> std::result::Result<Option<Account>, RpcError>;
+
+    /// Accounts owned by `program_id` with `data_size` bytes of data matching all `filters`
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        data_size: u64,
+        filters: &[MemcmpFilter],
+    ) -> std::result::Result<Vec<(Pubkey, Account)>, RpcError>;
+
+    async fn get_latest_blockhash(&self) -> std::result::Result<Hash, RpcError>;
+
+    async fn get_minimum_balance_for_rent_exemption(
+        &self,
+        data_len: usize,
+    ) -> std::result::Result<u64, RpcError>;
+
+    async fn send_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> std::result::Result<Signature, RpcError>;
+
+    /// `unitsConsumed` of simulating the unsigned `transaction`, simulate it without verifying
+    /// signatures and with the latest blockhash
+    async fn simulate_compute_units(
+        &self,
+        transaction: &Transaction,
+    ) -> std::result::Result<u64, RpcError>;
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+    Rpc(RpcError),
+    AccountNotFound(Pubkey),
+    /// Account isn't a valid Stream
+    InvalidStream(Pubkey, AccountError),
+    InvalidUpdate(UpdateError),
+    Signer(SignerError),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Rpc(e) => write!(f, "rpc error: {e}"),
+            ClientError::AccountNotFound(key) => write!(f, "account {key} not found"),
+            ClientError::InvalidStream(key, e) => write!(f, "account {key}: {e}"),
+            ClientError::InvalidUpdate(e) => write!(f, "invalid update: {e}"),
+            ClientError::Signer(e) => write!(f, "signer error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<RpcError> for ClientError {
+    fn from(e: RpcError) -> Self {
+        ClientError::Rpc(e)
+    }
+}
+
+impl From<SignerError> for ClientError {
+    fn from(e: SignerError) -> Self {
+        ClientError::Signer(e)
+    }
+}
+
+impl From<UpdateError> for ClientError {
+    fn from(e: UpdateError) -> Self {
+        ClientError::InvalidUpdate(e)
+    }
+}
+
+pub type ClientResult<T> = std::result::Result<T, ClientError>;
+
+/// Streams listed by `StreamflowClient::get_streams`
+#[derive(Debug, Default)]
+pub struct StreamList {
+    pub streams: Vec<(Pubkey, Contract)>,
+    /// Accounts matching the filters that aren't valid Streams, skipped from `streams`
+    pub invalid: Vec<(Pubkey, AccountError)>,
+}
+
+/// Client of the Streamflow program, `payer` of every transaction is its first signer
+pub struct StreamflowClient<R> {
+    rpc: R,
+}
+
+impl<R: StreamflowRpc> StreamflowClient<R> {
+    pub fn new(rpc: R) -> Self {
+        Self { rpc }
+    }
+
+    pub fn rpc(&self) -> &R {
+        &self.rpc
+    }
+
+    /// Fetch and decode the Stream stored in `metadata`
+    pub async fn get_stream(&self, metadata: &Pubkey) -> ClientResult<Contract> {
+        let account = self
+            .rpc
+            .get_account(metadata)
+            .await?
+            .ok_or(ClientError::AccountNotFound(*metadata))?;
+        load_contract_data(&account.owner, &account.data)
+            .map_err(|e| ClientError::InvalidStream(*metadata, e))
+    }
+
+    /// Fetch Streams matching all `filters`, accounts that don't decode are reported in
+    /// `StreamList::invalid` instead of failing the listing
+    pub async fn get_streams(&self, filters: &[MemcmpFilter]) -> ClientResult<StreamList> {
+        let accounts = self
+            .rpc
+            .get_program_accounts(&crate::id(), METADATA_LEN as u64, filters)
+            .await?;
+        let mut list = StreamList::default();
+        for (key, account) in accounts {
+            match load_contract_data(&account.owner, &account.data) {
+                Ok(contract) => list.streams.push((key, contract)),
+                Err(e) => list.invalid.push((key, e)),
+            }
+        }
+        Ok(list)
+    }
+
+    pub async fn streams_by_sender(&self, sender: &Pubkey) -> ClientResult<StreamList> {
+        self.get_streams(&[MemcmpFilter::pubkey(offset::SENDER, sender)])
+            .await
+    }
+
+    pub async fn streams_by_recipient(&self, recipient: &Pubkey) -> ClientResult<StreamList> {
+        self.get_streams(&[MemcmpFilter::pubkey(offset::RECIPIENT, recipient)])
+            .await
+    }
+
+    pub async fn streams_by_mint(&self, mint: &Pubkey) -> ClientResult<StreamList> {
+        self.get_streams(&[MemcmpFilter::pubkey(offset::MINT, mint)])
+            .await
+    }
+
+    /// Sign `instructions` with `signers` and send them in one transaction
+    pub async fn send(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&dyn Signer],
+    ) -> ClientResult<Signature> {
+        let payer = signers.first().ok_or(SignerError::NotEnoughSigners)?;
+        let mut transaction = Transaction::new_with_payer(instructions, Some(&payer.pubkey()));
+        let blockhash = self.rpc.get_latest_blockhash().await?;
+        transaction.try_sign(signers, blockhash)?;
+        Ok(self.rpc.send_transaction(&transaction).await?)
+    }
+
+    /// Compute units `instructions` consume in a transaction paid by `payer`, measured by
+    /// simulating it on the cluster. Use it to fill `compute_budget::ComputeUnits`.
+    pub async fn simulate_compute_units(
+        &self,
+        payer: &Pubkey,
+        instructions: &[Instruction],
+    ) -> ClientResult<u64> {
+        let transaction = Transaction::new_with_payer(instructions, Some(payer));
+        Ok(self.rpc.simulate_compute_units(&transaction).await?)
+    }
+
+    /// Create a Stream with `create`, `metadata` is a new keypair
+    pub async fn create(
+        &self,
+        sender: &dyn Signer,
+        metadata: &dyn Signer,
+        mint: Pubkey,
+        recipient: Pubkey,
+        partner: Pubkey,
+        params: &CreateParams,
+    ) -> ClientResult<Signature> {
+        let ix = instructions::create(
+            sender.pubkey(),
+            metadata.pubkey(),
+            mint,
+            recipient,
+            partner,
+            params,
+        );
+        self.send(&[ix], &[sender, metadata]).await
+    }
+
+    /// Create a Stream with `create_v2`, returns its metadata PDA
+    pub async fn create_v2(
+        &self,
+        sender: &dyn Signer,
+        mint: Pubkey,
+        recipient: Pubkey,
+        partner: Pubkey,
+        params: &CreateParams,
+    ) -> ClientResult<(Pubkey, Signature)> {
+        let ix = instructions::create_v2(sender.pubkey(), mint, recipient, partner, params);
+        let signature = self.send(&[ix], &[sender]).await?;
+        let (metadata, _) = derive_metadata(&mint, &sender.pubkey(), params.nonce, &crate::id());
+        Ok((metadata, signature))
+    }
+
+    /// `create_account` instruction allocating a metadata account for `create_unchecked` methods
+    async fn create_metadata_account(
+        &self,
+        payer: &Pubkey,
+        metadata: &Pubkey,
+    ) -> ClientResult<Instruction> {
+        let lamports = self
+            .rpc
+            .get_minimum_balance_for_rent_exemption(METADATA_LEN)
+            .await?;
+        Ok(create_account(
+            payer,
+            metadata,
+            lamports,
+            METADATA_LEN as u64,
+            &crate::id(),
+        ))
+    }
+
+    /// Create a Stream with `create_unchecked`, `metadata` is a new keypair the transaction
+    /// allocates and assigns to the program first
+    pub async fn create_unchecked(
+        &self,
+        sender: &dyn Signer,
+        metadata: &dyn Signer,
+        mint: Pubkey,
+        recipient: Pubkey,
+        partner: Pubkey,
+        params: &CreateParams,
+    ) -> ClientResult<Signature> {
+        let allocate = self
+            .create_metadata_account(&sender.pubkey(), &metadata.pubkey())
+            .await?;
+        let ix = instructions::create_unchecked(
+            sender.pubkey(),
+            metadata.pubkey(),
+            mint,
+            recipient,
+            partner,
+            params,
+        );
+        self.send(&[allocate, ix], &[sender, metadata]).await
+    }
+
+    /// Create a Stream with `create_unchecked_v2`, returns its metadata PDA
+    pub async fn create_unchecked_v2(
+        &self,
+        sender: &dyn Signer,
+        mint: Pubkey,
+        recipient: Pubkey,
+        partner: Pubkey,
+        params: &CreateParams,
+    ) -> ClientResult<(Pubkey, Signature)> {
+        let ix =
+            instructions::create_unchecked_v2(sender.pubkey(), mint, recipient, partner, params);
+        let signature = self.send(&[ix], &[sender]).await?;
+        let (metadata, _) = derive_metadata(&mint, &sender.pubkey(), params.nonce, &crate::id());
+        Ok((metadata, signature))
+    }
+
+    /// Create a Stream with `create_unchecked_with_payer`, `payer` funds the accounts and
+    /// `metadata` is a new keypair the transaction allocates and assigns to the program first
+    pub async fn create_unchecked_with_payer(
+        &self,
+        payer: &dyn Signer,
+        sender: &dyn Signer,
+        metadata: &dyn Signer,
+        mint: Pubkey,
+        recipient: Pubkey,
+        partner: Pubkey,
+        params: &CreateParams,
+    ) -> ClientResult<Signature> {
+        let allocate = self
+            .create_metadata_account(&payer.pubkey(), &metadata.pubkey())
+            .await?;
+        let ix = instructions::create_unchecked_with_payer(
+            payer.pubkey(),
+            sender.pubkey(),
+            metadata.pubkey(),
+            mint,
+            recipient,
+            partner,
+            params,
+        );
+        self.send(&[allocate, ix], &[payer, sender, metadata]).await
+    }
+
+    /// Create a Stream with `create_unchecked_with_payer_v2`, returns its metadata PDA derived
+    /// from `payer`
+    pub async fn create_unchecked_with_payer_v2(
+        &self,
+        payer: &dyn Signer,
+        sender: &dyn Signer,
+        mint: Pubkey,
+        recipient: Pubkey,
+        partner: Pubkey,
+        params: &CreateParams,
+    ) -> ClientResult<(Pubkey, Signature)> {
+        let ix = instructions::create_unchecked_with_payer_v2(
+            payer.pubkey(),
+            sender.pubkey(),
+            mint,
+            recipient,
+            partner,
+            params,
+        );
+        let signature = self.send(&[ix], &[payer, sender]).await?;
+        let (metadata, _) = derive_metadata(&mint, &payer.pubkey(), params.nonce, &crate::id());
+        Ok((metadata, signature))
+    }
+
+    pub async fn update(
+        &self,
+        sender: &dyn Signer,
+        metadata: Pubkey,
+        args: UpdateArgs,
+    ) -> ClientResult<Signature> {
+        let ix = update_instruction(sender.pubkey(), metadata, args)?;
+        self.send(&[ix], &[sender]).await
+    }
+
+    /// Withdraw `amount` from the Stream, `u64::MAX` withdraws everything available
+    pub async fn withdraw(
+        &self,
+        authority: &dyn Signer,
+        metadata: Pubkey,
+        amount: u64,
+    ) -> ClientResult<Signature> {
+        let contract = self.get_stream(&metadata).await?;
+        let ix = instructions::withdraw(authority.pubkey(), metadata, &contract, amount);
+        self.send(&[ix], &[authority]).await
+    }
+
+    pub async fn cancel(
+        &self,
+        authority: &dyn Signer,
+        metadata: Pubkey,
+    ) -> ClientResult<Signature> {
+        let contract = self.get_stream(&metadata).await?;
+        let ix = instructions::cancel(authority.pubkey(), metadata, &contract);
+        self.send(&[ix], &[authority]).await
+    }
+
+    pub async fn pause(&self, sender: &dyn Signer, metadata: Pubkey) -> ClientResult<Signature> {
+        let ix = instructions::pause(sender.pubkey(), metadata);
+        self.send(&[ix], &[sender]).await
+    }
+
+    pub async fn unpause(&self, sender: &dyn Signer, metadata: Pubkey) -> ClientResult<Signature> {
+        let ix = instructions::unpause(sender.pubkey(), metadata);
+        self.send(&[ix], &[sender]).await
+    }
+
+    pub async fn transfer_recipient(
+        &self,
+        authority: &dyn Signer,
+        metadata: Pubkey,
+        new_recipient: Pubkey,
+    ) -> ClientResult<Signature> {
+        let contract = self.get_stream(&metadata).await?;
+        let ix = instructions::transfer_recipient(
+            authority.pubkey(),
+            metadata,
+            contract.mint,
+            new_recipient,
+        );
+        self.send(&[ix], &[authority]).await
+    }
+
+    pub async fn transfer_sender(
+        &self,
+        sender: &dyn Signer,
+        new_sender: &dyn Signer,
+        metadata: Pubkey,
+    ) -> ClientResult<Signature> {
+        let contract = self.get_stream(&metadata).await?;
+        let ix = instructions::transfer_sender(
+            sender.pubkey(),
+            new_sender.pubkey(),
+            metadata,
+            contract.mint,
+        );
+        self.send(&[ix], &[sender, new_sender]).await
+    }
+
+    pub async fn topup(
+        &self,
+        sender: &dyn Signer,
+        metadata: Pubkey,
+        amount: u64,
+    ) -> ClientResult<Signature> {
+        let contract = self.get_stream(&metadata).await?;
+        let ix = instructions::topup(sender.pubkey(), metadata, &contract, amount);
+        self.send(&[ix], &[sender]).await
+    }
+}
@@ -0,0 +1,170 @@
+//! Compute-unit budgets for Streamflow instructions
+//!
+//! Compute units a method consumes depend on the deployment, the accounts it creates and the
+//! token program. `ComputeUnits::default()` holds conservative upper bounds, they aren't
+//! benchmarks of the deployed program. To budget closer to the real cost, measure the methods
+//! you use with `StreamflowClient::simulate_compute_units` (`client` feature), which simulates a
+//! transaction on the cluster and reads `unitsConsumed`, then add headroom for account state that
+//! differs between Streams, e.g. token accounts that have to be created.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::Discriminator;
+
+use crate::instruction;
+use crate::state::Cluster;
+
+/// Address of the Compute Budget program
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey =
+    Pubkey::from_str_const("ComputeBudget111111111111111111111111111111");
+/// Max compute units a transaction can request
+pub const MAX_COMPUTE_UNITS: u32 = 1_400_000;
+
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// Compute units budgeted for every Streamflow method
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ComputeUnits {
+    /// `create` and `create_v2`
+    pub create: u32,
+    /// `create_unchecked` and `create_unchecked_with_payer` and their v2 versions
+    pub create_unchecked: u32,
+    pub withdraw: u32,
+    pub cancel: u32,
+    pub topup: u32,
+    pub pause: u32,
+    pub unpause: u32,
+    pub update: u32,
+    pub transfer_recipient: u32,
+    pub transfer_sender: u32,
+}
+
+/// Upper bounds of a single top-level instruction, with headroom for the token transfers, account
+/// creations and PDA derivations each method performs
+impl Default for ComputeUnits {
+    fn default() -> Self {
+        Self {
+            // also creates recipient, treasury and partner token accounts
+            create: 150_000,
+            create_unchecked: 100_000,
+            // may create recipient, treasury and partner token accounts
+            withdraw: 100_000,
+            // withdraws unlocked tokens and returns the rest to the sender
+            cancel: 120_000,
+            topup: 60_000,
+            pause: 20_000,
+            unpause: 20_000,
+            update: 40_000,
+            // may create token account of the new recipient
+            transfer_recipient: 60_000,
+            transfer_sender: 20_000,
+        }
+    }
+}
+
+/// Compute units `units` budgets for a Streamflow instruction, `None` for other instructions
+pub fn budgeted_compute_units(ix: &Instruction, units: &ComputeUnits) -> Option<u32> {
+    if ix.program_id != Cluster::Mainnet.program_id()
+        && ix.program_id != Cluster::Devnet.program_id()
+    {
+        return None;
+    }
+    let discriminator = ix.data.get(..8)?;
+    let units = match discriminator {
+        d if d == instruction::Create::DISCRIMINATOR
+            || d == instruction::CreateV2::DISCRIMINATOR =>
+        {
+            units.create
+        }
+        d if d == instruction::CreateUnchecked::DISCRIMINATOR
+            || d == instruction::CreateUncheckedV2::DISCRIMINATOR
+            || d == instruction::CreateUncheckedWithPayer::DISCRIMINATOR
+            || d == instruction::CreateUncheckedWithPayerV2::DISCRIMINATOR =>
+        {
+            units.create_unchecked
+        }
+        d if d == instruction::Withdraw::DISCRIMINATOR => units.withdraw,
+        d if d == instruction::Cancel::DISCRIMINATOR => units.cancel,
+        d if d == instruction::Topup::DISCRIMINATOR => units.topup,
+        d if d == instruction::Pause::DISCRIMINATOR => units.pause,
+        d if d == instruction::Unpause::DISCRIMINATOR => units.unpause,
+        d if d == instruction::Update::DISCRIMINATOR => units.update,
+        d if d == instruction::TransferRecipient::DISCRIMINATOR => units.transfer_recipient,
+        d if d == instruction::TransferSender::DISCRIMINATOR => units.transfer_sender,
+        _ => return None,
+    };
+    Some(units)
+}
+
+/// Build `SetComputeUnitLimit` instruction of the Compute Budget program
+pub fn set_compute_unit_limit(units: u32) -> Instruction {
+    let mut data = vec![SET_COMPUTE_UNIT_LIMIT];
+    data.extend_from_slice(&units.to_le_bytes());
+    Instruction {
+        program_id: COMPUTE_BUDGET_PROGRAM_ID,
+        accounts: vec![],
+        data,
+    }
+}
+
+/// Build `SetComputeUnitPrice` instruction of the Compute Budget program
+pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+    let mut data = vec![SET_COMPUTE_UNIT_PRICE];
+    data.extend_from_slice(&micro_lamports.to_le_bytes());
+    Instruction {
+        program_id: COMPUTE_BUDGET_PROGRAM_ID,
+        accounts: vec![],
+        data,
+    }
+}
+
+fn is_compute_unit_limit(ix: &Instruction) -> bool {
+    ix.program_id == COMPUTE_BUDGET_PROGRAM_ID && ix.data.first() == Some(&SET_COMPUTE_UNIT_LIMIT)
+}
+
+/// Units requested by a `SetComputeUnitLimit` instruction
+fn compute_unit_limit(ix: &Instruction) -> Option<u32> {
+    if !is_compute_unit_limit(ix) {
+        return None;
+    }
+    let units = ix.data.get(1..5)?.try_into().ok()?;
+    Some(u32::from_le_bytes(units))
+}
+
+/// Set `SetComputeUnitLimit` covering Streamflow instructions in `instructions`.
+///
+/// `extra_units` is added for the rest of the instructions, the limit is capped at
+/// `MAX_COMPUTE_UNITS`. An existing `SetComputeUnitLimit` is replaced in place by the larger of
+/// its limit and the budgeted one, otherwise the limit is prepended.
+pub fn with_compute_budget(
+    instructions: &[Instruction],
+    units: &ComputeUnits,
+    extra_units: u32,
+) -> Vec<Instruction> {
+    let limit = instructions
+        .iter()
+        .filter_map(|ix| budgeted_compute_units(ix, units))
+        .fold(extra_units, u32::saturating_add)
+        .min(MAX_COMPUTE_UNITS);
+    let mut result = Vec::with_capacity(instructions.len() + 1);
+    match instructions.iter().position(is_compute_unit_limit) {
+        Some(position) => {
+            let existing = compute_unit_limit(&instructions[position]).unwrap_or_default();
+            result.extend_from_slice(&instructions[..position]);
+            result.push(set_compute_unit_limit(limit.max(existing)));
+            // a transaction with several limits fails, drop the rest
+            result.extend(
+                instructions[position + 1..]
+                    .iter()
+                    .filter(|ix| !is_compute_unit_limit(ix))
+                    .cloned(),
+            );
+        }
+        None => {
+            result.push(set_compute_unit_limit(limit));
+            result.extend_from_slice(instructions);
+        }
+    }
+    result
+}
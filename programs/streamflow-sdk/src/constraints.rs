@@ -0,0 +1,371 @@
+//! Validation of account sets passed to Streamflow instructions
+//!
+//! `Accounts` structs of the program accept most accounts unchecked, programs invoking Streamflow
+//! can use these helpers to reject wrong accounts before the CPI. Boolean helpers fit Anchor
+//! `constraint = ...` expressions, `ValidateAccounts` loads the Stream from the passed metadata and
+//! checks a whole CPI account set at once, `ValidateKeys` checks keys of an instruction against a
+//! Stream fetched off chain.
+
+use std::fmt;
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+
+use crate::state::{find_escrow_account, Contract, FEE_ORACLE, STREAMFLOW_TREASURY, WITHDRAWOR};
+
+/// Account that doesn't match the Stream or the protocol constants
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountError {
+    /// Metadata isn't owned by the Streamflow program
+    InvalidMetadataOwner,
+    /// Metadata can't be deserialized into a `Contract` or isn't initialized
+    InvalidMetadata,
+    /// Escrow isn't the PDA of the metadata
+    EscrowMismatch,
+    /// Authority isn't allowed to withdraw from or cancel the Stream
+    AuthorityMismatch,
+    SenderMismatch,
+    SenderTokensMismatch,
+    RecipientMismatch,
+    RecipientTokensMismatch,
+    MintMismatch,
+    PartnerMismatch,
+    PartnerTokensMismatch,
+    /// Streamflow treasury isn't `STRM_TREASURY`
+    StreamflowTreasuryMismatch,
+    StreamflowTreasuryTokensMismatch,
+    /// Withdrawor isn't `WITHDRAWOR_ADDRESS`
+    WithdraworMismatch,
+    InvalidTokenProgram,
+    InvalidSystemProgram,
+}
+
+impl fmt::Display for AccountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            AccountError::InvalidMetadataOwner => "metadata isn't owned by the Streamflow program",
+            AccountError::InvalidMetadata => "metadata isn't a valid Stream",
+            AccountError::EscrowMismatch => "escrow doesn't match the Stream",
+            AccountError::AuthorityMismatch => "authority isn't allowed to act on the Stream",
+            AccountError::SenderMismatch => "sender doesn't match the Stream",
+            AccountError::SenderTokensMismatch => "sender tokens don't match the Stream",
+            AccountError::RecipientMismatch => "recipient doesn't match the Stream",
+            AccountError::RecipientTokensMismatch => "recipient tokens don't match the Stream",
+            AccountError::MintMismatch => "mint doesn't match the Stream",
+            AccountError::PartnerMismatch => "partner doesn't match the Stream",
+            AccountError::PartnerTokensMismatch => "partner tokens don't match the Stream",
+            AccountError::StreamflowTreasuryMismatch => "invalid Streamflow treasury",
+            AccountError::StreamflowTreasuryTokensMismatch => {
+                "Streamflow treasury tokens don't match the Stream"
+            }
+            AccountError::WithdraworMismatch => "invalid withdrawor",
+            AccountError::InvalidTokenProgram => "invalid token program",
+            AccountError::InvalidSystemProgram => "invalid system program",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for AccountError {}
+
+pub fn is_streamflow_treasury(key: &Pubkey) -> bool {
+    *key == STREAMFLOW_TREASURY
+}
+
+pub fn is_withdrawor(key: &Pubkey) -> bool {
+    *key == WITHDRAWOR
+}
+
+pub fn is_fee_oracle(key: &Pubkey) -> bool {
+    *key == FEE_ORACLE
+}
+
+/// Whether `tokens` is a token account of `owner` for `mint`, not necessarily the associated one
+pub fn is_token_account(tokens: &AccountInfo, owner: &Pubkey, mint: &Pubkey) -> bool {
+    if *tokens.owner != anchor_spl::token::ID {
+        return false;
+    }
+    let Ok(data) = tokens.try_borrow_data() else {
+        return false;
+    };
+    TokenAccount::try_deserialize(&mut &data[..])
+        .is_ok_and(|account| account.owner == *owner && account.mint == *mint)
+}
+
+/// Whether the account is owned by the Streamflow program
+pub fn is_streamflow_metadata(metadata: &AccountInfo) -> bool {
+    *metadata.owner == crate::id()
+}
+
+/// Whether `escrow` is the escrow PDA of the Stream stored in `metadata`.
+///
+/// Streams migrated from an old metadata account keep the escrow derived from `old_metadata`.
+pub fn is_escrow_account(escrow: &Pubkey, metadata: &Pubkey, contract: &Contract) -> bool {
+    let seed = if contract.old_metadata == Pubkey::default() {
+        metadata
+    } else {
+        &contract.old_metadata
+    };
+    find_escrow_account(seed.as_ref(), &crate::id()).0 == *escrow
+}
+
+/// Whether `authority` can withdraw from the Stream, anyone can with automatic withdrawals
+pub fn can_withdraw(authority: &Pubkey, contract: &Contract) -> bool {
+    *authority == contract.recipient || contract.ix.automatic_withdrawal
+}
+
+/// Whether `authority` can cancel the Stream
+pub fn can_cancel(authority: &Pubkey, contract: &Contract) -> bool {
+    (*authority == contract.sender && contract.ix.cancelable_by_sender)
+        || (*authority == contract.recipient && contract.ix.cancelable_by_recipient)
+}
+
+/// Deserialize a Stream from an account, checking that it's owned by the Streamflow program and
+/// initialized
+pub fn load_contract(metadata: &AccountInfo) -> std::result::Result<Contract, AccountError> {
+    let data = metadata
+        .try_borrow_data()
+        .map_err(|_| AccountError::InvalidMetadata)?;
+    load_contract_data(metadata.owner, &data)
+}
+
+/// `load_contract` for an account fetched off chain, given its owner and data
+pub fn load_contract_data(
+    owner: &Pubkey,
+    data: &[u8],
+) -> std::result::Result<Contract, AccountError> {
+    check(*owner == crate::id(), AccountError::InvalidMetadataOwner)?;
+    let contract =
+        Contract::deserialize(&mut &data[..]).map_err(|_| AccountError::InvalidMetadata)?;
+    // zeroed metadata, e.g. allocated but not yet written or closed
+    check(contract.magic != 0, AccountError::InvalidMetadata)?;
+    Ok(contract)
+}
+
+fn check(valid: bool, error: AccountError) -> std::result::Result<(), AccountError> {
+    if valid {
+        Ok(())
+    } else {
+        Err(error)
+    }
+}
+
+fn check_fee_accounts(
+    contract: &Contract,
+    streamflow_treasury: &Pubkey,
+    streamflow_treasury_tokens: &Pubkey,
+    partner: &Pubkey,
+    partner_tokens: &Pubkey,
+) -> std::result::Result<(), AccountError> {
+    check(
+        is_streamflow_treasury(streamflow_treasury),
+        AccountError::StreamflowTreasuryMismatch,
+    )?;
+    check(
+        *streamflow_treasury_tokens == contract.streamflow_treasury_tokens,
+        AccountError::StreamflowTreasuryTokensMismatch,
+    )?;
+    check(*partner == contract.partner, AccountError::PartnerMismatch)?;
+    check(
+        *partner_tokens == contract.partner_tokens,
+        AccountError::PartnerTokensMismatch,
+    )
+}
+
+fn check_escrow(
+    escrow: &Pubkey,
+    metadata: &Pubkey,
+    contract: &Contract,
+) -> std::result::Result<(), AccountError> {
+    check(
+        *escrow == contract.escrow_tokens && is_escrow_account(escrow, metadata, contract),
+        AccountError::EscrowMismatch,
+    )
+}
+
+/// Keys of a Streamflow instruction that can be checked against a Stream
+pub trait ValidateKeys {
+    /// Check keys against `contract` stored in the metadata and protocol constants
+    fn validate_keys(&self, contract: &Contract) -> std::result::Result<(), AccountError>;
+}
+
+/// Account set of a Streamflow CPI that can be checked against the Stream it passes
+pub trait ValidateAccounts {
+    /// Load the Stream from the passed metadata with `load_contract` and check the rest of the
+    /// accounts against it
+    fn validate(&self) -> std::result::Result<Contract, AccountError>;
+}
+
+impl ValidateKeys for crate::accounts::Withdraw {
+    fn validate_keys(&self, contract: &Contract) -> std::result::Result<(), AccountError> {
+        check(
+            can_withdraw(&self.authority, contract),
+            AccountError::AuthorityMismatch,
+        )?;
+        check(
+            self.recipient == contract.recipient,
+            AccountError::RecipientMismatch,
+        )?;
+        check(
+            self.recipient_tokens == contract.recipient_tokens,
+            AccountError::RecipientTokensMismatch,
+        )?;
+        check_escrow(&self.escrow_tokens, &self.metadata, contract)?;
+        check(self.mint == contract.mint, AccountError::MintMismatch)?;
+        check_fee_accounts(
+            contract,
+            &self.streamflow_treasury,
+            &self.streamflow_treasury_tokens,
+            &self.partner,
+            &self.partner_tokens,
+        )?;
+        check(
+            self.token_program == anchor_spl::token::ID,
+            AccountError::InvalidTokenProgram,
+        )
+    }
+}
+
+impl ValidateKeys for crate::accounts::Cancel {
+    fn validate_keys(&self, contract: &Contract) -> std::result::Result<(), AccountError> {
+        check(
+            can_cancel(&self.authority, contract),
+            AccountError::AuthorityMismatch,
+        )?;
+        check(self.sender == contract.sender, AccountError::SenderMismatch)?;
+        check(
+            self.sender_tokens == contract.sender_tokens,
+            AccountError::SenderTokensMismatch,
+        )?;
+        check(
+            self.recipient == contract.recipient,
+            AccountError::RecipientMismatch,
+        )?;
+        check(
+            self.recipient_tokens == contract.recipient_tokens,
+            AccountError::RecipientTokensMismatch,
+        )?;
+        check_escrow(&self.escrow_tokens, &self.metadata, contract)?;
+        check(self.mint == contract.mint, AccountError::MintMismatch)?;
+        check_fee_accounts(
+            contract,
+            &self.streamflow_treasury,
+            &self.streamflow_treasury_tokens,
+            &self.partner,
+            &self.partner_tokens,
+        )?;
+        check(
+            self.token_program == anchor_spl::token::ID,
+            AccountError::InvalidTokenProgram,
+        )
+    }
+}
+
+impl ValidateKeys for crate::accounts::Topup {
+    fn validate_keys(&self, contract: &Contract) -> std::result::Result<(), AccountError> {
+        check(self.sender == contract.sender, AccountError::SenderMismatch)?;
+        // any token account of the sender can fund a topup, owner and mint of `sender_tokens`
+        // are only known on chain and checked by `ValidateAccounts`
+        check_escrow(&self.escrow_tokens, &self.metadata, contract)?;
+        check(self.mint == contract.mint, AccountError::MintMismatch)?;
+        check(
+            is_withdrawor(&self.withdrawor),
+            AccountError::WithdraworMismatch,
+        )?;
+        check_fee_accounts(
+            contract,
+            &self.streamflow_treasury,
+            &self.streamflow_treasury_tokens,
+            &self.partner,
+            &self.partner_tokens,
+        )?;
+        check(
+            self.token_program == anchor_spl::token::ID,
+            AccountError::InvalidTokenProgram,
+        )?;
+        check(
+            self.system_program == anchor_lang::system_program::ID,
+            AccountError::InvalidSystemProgram,
+        )
+    }
+}
+
+/// Implement `ValidateAccounts` for CPI accounts by loading the metadata and validating their
+/// keys, followed by an optional check of account data
+#[cfg(feature = "cpi")]
+macro_rules! validate_cpi_accounts {
+    ($name:ident, [$($field:ident),* $(,)?] $(, $check_data:expr)?) => {
+        impl ValidateAccounts for crate::cpi::accounts::$name<'_> {
+            fn validate(&self) -> std::result::Result<Contract, AccountError> {
+                let contract = load_contract(&self.metadata)?;
+                crate::accounts::$name {
+                    $($field: self.$field.key(),)*
+                }
+                .validate_keys(&contract)?;
+                $(($check_data)(self, &contract)?;)?
+                Ok(contract)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "cpi")]
+validate_cpi_accounts!(
+    Withdraw,
+    [
+        authority,
+        recipient,
+        recipient_tokens,
+        metadata,
+        escrow_tokens,
+        streamflow_treasury,
+        streamflow_treasury_tokens,
+        partner,
+        partner_tokens,
+        mint,
+        token_program,
+    ]
+);
+
+#[cfg(feature = "cpi")]
+validate_cpi_accounts!(
+    Cancel,
+    [
+        authority,
+        sender,
+        sender_tokens,
+        recipient,
+        recipient_tokens,
+        metadata,
+        escrow_tokens,
+        streamflow_treasury,
+        streamflow_treasury_tokens,
+        partner,
+        partner_tokens,
+        mint,
+        token_program,
+    ]
+);
+
+#[cfg(feature = "cpi")]
+validate_cpi_accounts!(
+    Topup,
+    [
+        sender,
+        sender_tokens,
+        metadata,
+        escrow_tokens,
+        streamflow_treasury,
+        streamflow_treasury_tokens,
+        withdrawor,
+        partner,
+        partner_tokens,
+        mint,
+        token_program,
+        system_program,
+    ],
+    |accounts: &Self, contract: &Contract| check(
+        is_token_account(&accounts.sender_tokens, &contract.sender, &contract.mint),
+        AccountError::SenderTokensMismatch,
+    )
+);
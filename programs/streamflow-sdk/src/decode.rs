@@ -0,0 +1,489 @@
+use std::fmt;
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::{accounts, instruction};
+
+/// Reasons why instruction data can't be decoded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// Discriminator doesn't match any method of the program
+    UnknownInstruction,
+    /// Arguments can't be deserialized
+    InvalidData,
+    /// Less accounts than the instruction expects
+    NotEnoughAccounts,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            DecodeError::UnknownInstruction => "unknown instruction discriminator",
+            DecodeError::InvalidData => "invalid instruction data",
+            DecodeError::NotEnoughAccounts => "not enough accounts",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Decoded instruction of the Streamflow protocol with its arguments and named accounts
+#[derive(Debug)]
+pub enum StreamflowInstruction {
+    Create {
+        args: instruction::Create,
+        accounts: accounts::Create,
+    },
+    CreateV2 {
+        args: instruction::CreateV2,
+        accounts: accounts::Create,
+    },
+    CreateUnchecked {
+        args: instruction::CreateUnchecked,
+        accounts: accounts::CreateUnchecked,
+    },
+    CreateUncheckedV2 {
+        args: instruction::CreateUncheckedV2,
+        accounts: accounts::CreateUnchecked,
+    },
+    CreateUncheckedWithPayer {
+        args: instruction::CreateUncheckedWithPayer,
+        accounts: accounts::CreateUncheckedWithPayer,
+    },
+    CreateUncheckedWithPayerV2 {
+        args: instruction::CreateUncheckedWithPayerV2,
+        accounts: accounts::CreateUncheckedWithPayer,
+    },
+    Update {
+        args: instruction::Update,
+        accounts: accounts::Update,
+    },
+    Withdraw {
+        args: instruction::Withdraw,
+        accounts: accounts::Withdraw,
+    },
+    Cancel {
+        accounts: accounts::Cancel,
+    },
+    Pause {
+        accounts: accounts::Pause,
+    },
+    Unpause {
+        accounts: accounts::UnPause,
+    },
+    TransferRecipient {
+        accounts: accounts::Transfer,
+    },
+    TransferSender {
+        accounts: accounts::TransferSender,
+    },
+    Topup {
+        args: instruction::Topup,
+        accounts: accounts::Topup,
+    },
+}
+
+macro_rules! impl_debug {
+    ($module:ident::$ty:ident { $($field:ident),* $(,)? }) => {
+        impl fmt::Debug for $module::$ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($ty))
+                    $(.field(stringify!($field), &self.$field))*
+                    .finish()
+            }
+        }
+    };
+}
+
+/// Accounts of each instruction in the order the program expects them
+macro_rules! account_order {
+    ($($ty:ident { $($field:ident),* $(,)? })*) => {$(
+        impl accounts::$ty {
+            fn from_keys(keys: &[Pubkey]) -> std::result::Result<Self, DecodeError> {
+                let mut keys = keys.iter();
+                Ok(Self {
+                    $($field: *keys.next().ok_or(DecodeError::NotEnoughAccounts)?,)*
+                })
+            }
+        }
+
+        impl_debug!(accounts::$ty { $($field),* });
+    )*};
+}
+
+account_order! {
+    Create {
+        sender,
+        sender_tokens,
+        recipient,
+        metadata,
+        escrow_tokens,
+        recipient_tokens,
+        streamflow_treasury,
+        streamflow_treasury_tokens,
+        withdrawor,
+        partner,
+        partner_tokens,
+        mint,
+        fee_oracle,
+        rent,
+        timelock_program,
+        token_program,
+        associated_token_program,
+        system_program,
+    }
+    CreateUnchecked {
+        sender,
+        sender_tokens,
+        metadata,
+        escrow_tokens,
+        withdrawor,
+        mint,
+        fee_oracle,
+        rent,
+        timelock_program,
+        token_program,
+        system_program,
+    }
+    CreateUncheckedWithPayer {
+        payer,
+        sender,
+        sender_tokens,
+        metadata,
+        escrow_tokens,
+        withdrawor,
+        mint,
+        fee_oracle,
+        rent,
+        timelock_program,
+        token_program,
+        system_program,
+    }
+    Update {
+        sender,
+        metadata,
+        withdrawor,
+        system_program,
+    }
+    Withdraw {
+        authority,
+        recipient,
+        recipient_tokens,
+        metadata,
+        escrow_tokens,
+        streamflow_treasury,
+        streamflow_treasury_tokens,
+        partner,
+        partner_tokens,
+        mint,
+        token_program,
+    }
+    Cancel {
+        authority,
+        sender,
+        sender_tokens,
+        recipient,
+        recipient_tokens,
+        metadata,
+        escrow_tokens,
+        streamflow_treasury,
+        streamflow_treasury_tokens,
+        partner,
+        partner_tokens,
+        mint,
+        token_program,
+    }
+    Pause { sender, metadata }
+    UnPause { sender, metadata }
+    Transfer {
+        authority,
+        new_recipient,
+        new_recipient_tokens,
+        metadata,
+        mint,
+        rent,
+        token_program,
+        associated_token_program,
+        system_program,
+    }
+    TransferSender {
+        sender,
+        new_sender,
+        new_sender_tokens,
+        metadata,
+        mint,
+        token_program,
+    }
+    Topup {
+        sender,
+        sender_tokens,
+        metadata,
+        escrow_tokens,
+        streamflow_treasury,
+        streamflow_treasury_tokens,
+        withdrawor,
+        partner,
+        partner_tokens,
+        mint,
+        token_program,
+        system_program,
+    }
+}
+
+impl_debug!(instruction::Create {
+    start_time,
+    net_amount_deposited,
+    period,
+    amount_per_period,
+    cliff,
+    cliff_amount,
+    cancelable_by_sender,
+    cancelable_by_recipient,
+    automatic_withdrawal,
+    transferable_by_sender,
+    transferable_by_recipient,
+    can_topup,
+    stream_name,
+    withdraw_frequency,
+    pausable,
+    can_update_rate,
+});
+impl_debug!(instruction::CreateV2 {
+    start_time,
+    net_amount_deposited,
+    period,
+    amount_per_period,
+    cliff,
+    cliff_amount,
+    cancelable_by_sender,
+    cancelable_by_recipient,
+    automatic_withdrawal,
+    transferable_by_sender,
+    transferable_by_recipient,
+    can_topup,
+    stream_name,
+    withdraw_frequency,
+    pausable,
+    can_update_rate,
+    nonce,
+});
+impl_debug!(instruction::CreateUnchecked {
+    start_time,
+    net_amount_deposited,
+    period,
+    amount_per_period,
+    cliff,
+    cliff_amount,
+    cancelable_by_sender,
+    cancelable_by_recipient,
+    automatic_withdrawal,
+    transferable_by_sender,
+    transferable_by_recipient,
+    can_topup,
+    stream_name,
+    withdraw_frequency,
+    recipient,
+    partner,
+    pausable,
+    can_update_rate,
+});
+impl_debug!(instruction::CreateUncheckedV2 {
+    start_time,
+    net_amount_deposited,
+    period,
+    amount_per_period,
+    cliff,
+    cliff_amount,
+    cancelable_by_sender,
+    cancelable_by_recipient,
+    automatic_withdrawal,
+    transferable_by_sender,
+    transferable_by_recipient,
+    can_topup,
+    stream_name,
+    withdraw_frequency,
+    recipient,
+    partner,
+    pausable,
+    can_update_rate,
+    nonce,
+});
+impl_debug!(instruction::CreateUncheckedWithPayer {
+    start_time,
+    net_amount_deposited,
+    period,
+    amount_per_period,
+    cliff,
+    cliff_amount,
+    cancelable_by_sender,
+    cancelable_by_recipient,
+    automatic_withdrawal,
+    transferable_by_sender,
+    transferable_by_recipient,
+    can_topup,
+    stream_name,
+    withdraw_frequency,
+    recipient,
+    partner,
+    pausable,
+    can_update_rate,
+});
+impl_debug!(instruction::CreateUncheckedWithPayerV2 {
+    start_time,
+    net_amount_deposited,
+    period,
+    amount_per_period,
+    cliff,
+    cliff_amount,
+    cancelable_by_sender,
+    cancelable_by_recipient,
+    automatic_withdrawal,
+    transferable_by_sender,
+    transferable_by_recipient,
+    can_topup,
+    stream_name,
+    withdraw_frequency,
+    recipient,
+    partner,
+    pausable,
+    can_update_rate,
+    nonce,
+});
+impl_debug!(instruction::Update {
+    enable_automatic_withdrawal,
+    withdraw_frequency,
+    amount_per_period,
+    transferable_by_sender,
+    transferable_by_recipient,
+    cancelable_by_sender,
+});
+impl_debug!(instruction::Withdraw { amount });
+impl_debug!(instruction::Topup { amount });
+
+fn args<T: AnchorDeserialize>(mut data: &[u8]) -> std::result::Result<T, DecodeError> {
+    T::deserialize(&mut data).map_err(|_| DecodeError::InvalidData)
+}
+
+/// Decode instruction `data` and its `accounts` (in instruction order) into a typed instruction.
+///
+/// Accounts past the ones expected by the instruction are ignored.
+pub fn decode_instruction(
+    data: &[u8],
+    accounts: &[Pubkey],
+) -> std::result::Result<StreamflowInstruction, DecodeError> {
+    if data.len() < 8 {
+        return Err(DecodeError::UnknownInstruction);
+    }
+    let (discriminator, data) = data.split_at(8);
+    let ix = match discriminator {
+        d if d == instruction::Create::DISCRIMINATOR => StreamflowInstruction::Create {
+            args: args(data)?,
+            accounts: accounts::Create::from_keys(accounts)?,
+        },
+        d if d == instruction::CreateV2::DISCRIMINATOR => StreamflowInstruction::CreateV2 {
+            args: args(data)?,
+            accounts: accounts::Create::from_keys(accounts)?,
+        },
+        d if d == instruction::CreateUnchecked::DISCRIMINATOR => {
+            StreamflowInstruction::CreateUnchecked {
+                args: args(data)?,
+                accounts: accounts::CreateUnchecked::from_keys(accounts)?,
+            }
+        }
+        d if d == instruction::CreateUncheckedV2::DISCRIMINATOR => {
+            StreamflowInstruction::CreateUncheckedV2 {
+                args: args(data)?,
+                accounts: accounts::CreateUnchecked::from_keys(accounts)?,
+            }
+        }
+        d if d == instruction::CreateUncheckedWithPayer::DISCRIMINATOR => {
+            StreamflowInstruction::CreateUncheckedWithPayer {
+                args: args(data)?,
+                accounts: accounts::CreateUncheckedWithPayer::from_keys(accounts)?,
+            }
+        }
+        d if d == instruction::CreateUncheckedWithPayerV2::DISCRIMINATOR => {
+            StreamflowInstruction::CreateUncheckedWithPayerV2 {
+                args: args(data)?,
+                accounts: accounts::CreateUncheckedWithPayer::from_keys(accounts)?,
+            }
+        }
+        d if d == instruction::Update::DISCRIMINATOR => StreamflowInstruction::Update {
+            args: args(data)?,
+            accounts: accounts::Update::from_keys(accounts)?,
+        },
+        d if d == instruction::Withdraw::DISCRIMINATOR => StreamflowInstruction::Withdraw {
+            args: args(data)?,
+            accounts: accounts::Withdraw::from_keys(accounts)?,
+        },
+        d if d == instruction::Cancel::DISCRIMINATOR => StreamflowInstruction::Cancel {
+            accounts: accounts::Cancel::from_keys(accounts)?,
+        },
+        d if d == instruction::Pause::DISCRIMINATOR => StreamflowInstruction::Pause {
+            accounts: accounts::Pause::from_keys(accounts)?,
+        },
+        d if d == instruction::Unpause::DISCRIMINATOR => StreamflowInstruction::Unpause {
+            accounts: accounts::UnPause::from_keys(accounts)?,
+        },
+        d if d == instruction::TransferRecipient::DISCRIMINATOR => {
+            StreamflowInstruction::TransferRecipient {
+                accounts: accounts::Transfer::from_keys(accounts)?,
+            }
+        }
+        d if d == instruction::TransferSender::DISCRIMINATOR => {
+            StreamflowInstruction::TransferSender {
+                accounts: accounts::TransferSender::from_keys(accounts)?,
+            }
+        }
+        d if d == instruction::Topup::DISCRIMINATOR => StreamflowInstruction::Topup {
+            args: args(data)?,
+            accounts: accounts::Topup::from_keys(accounts)?,
+        },
+        _ => return Err(DecodeError::UnknownInstruction),
+    };
+    Ok(ix)
+}
+
+impl StreamflowInstruction {
+    /// Name of the program method, e.g. `create_v2`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Create { .. } => "create",
+            Self::CreateV2 { .. } => "create_v2",
+            Self::CreateUnchecked { .. } => "create_unchecked",
+            Self::CreateUncheckedV2 { .. } => "create_unchecked_v2",
+            Self::CreateUncheckedWithPayer { .. } => "create_unchecked_with_payer",
+            Self::CreateUncheckedWithPayerV2 { .. } => "create_unchecked_with_payer_v2",
+            Self::Update { .. } => "update",
+            Self::Withdraw { .. } => "withdraw",
+            Self::Cancel { .. } => "cancel",
+            Self::Pause { .. } => "pause",
+            Self::Unpause { .. } => "unpause",
+            Self::TransferRecipient { .. } => "transfer_recipient",
+            Self::TransferSender { .. } => "transfer_sender",
+            Self::Topup { .. } => "topup",
+        }
+    }
+
+    /// Metadata account of the Stream the instruction operates on
+    pub fn metadata(&self) -> Pubkey {
+        match self {
+            Self::Create { accounts, .. } | Self::CreateV2 { accounts, .. } => accounts.metadata,
+            Self::CreateUnchecked { accounts, .. } | Self::CreateUncheckedV2 { accounts, .. } => {
+                accounts.metadata
+            }
+            Self::CreateUncheckedWithPayer { accounts, .. }
+            | Self::CreateUncheckedWithPayerV2 { accounts, .. } => accounts.metadata,
+            Self::Update { accounts, .. } => accounts.metadata,
+            Self::Withdraw { accounts, .. } => accounts.metadata,
+            Self::Cancel { accounts } => accounts.metadata,
+            Self::Pause { accounts } => accounts.metadata,
+            Self::Unpause { accounts } => accounts.metadata,
+            Self::TransferRecipient { accounts } => accounts.metadata,
+            Self::TransferSender { accounts } => accounts.metadata,
+            Self::Topup { accounts, .. } => accounts.metadata,
+        }
+    }
+}
@@ -0,0 +1,368 @@
+//! Builders of Streamflow protocol instructions for off-chain clients
+//!
+//! Accounts that can be derived are derived: escrow from the metadata, token accounts as
+//! associated token accounts of their owners. Builders of methods acting on an existing Stream
+//! take its `Contract` to fill the accounts it stores.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::InstructionData;
+use anchor_spl::associated_token::get_associated_token_address;
+
+use crate::state::{
+    derive_metadata, find_escrow_account, Contract, CreateParams, FEE_ORACLE_ADDRESS,
+    STRM_TREASURY, WITHDRAWOR_ADDRESS,
+};
+
+/// Instruction data of a create method, fields of `CreateParams` plus the listed ones
+macro_rules! create_data {
+    ($ty:ident, $params:expr, { $($field:ident: $value:expr),* $(,)? }) => {{
+        let params: &CreateParams = $params;
+        crate::instruction::$ty {
+            start_time: params.start_time,
+            net_amount_deposited: params.net_amount_deposited,
+            period: params.period,
+            amount_per_period: params.amount_per_period,
+            cliff: params.cliff,
+            cliff_amount: params.cliff_amount,
+            cancelable_by_sender: params.cancelable_by_sender,
+            cancelable_by_recipient: params.cancelable_by_recipient,
+            automatic_withdrawal: params.automatic_withdrawal,
+            transferable_by_sender: params.transferable_by_sender,
+            transferable_by_recipient: params.transferable_by_recipient,
+            can_topup: params.can_topup,
+            stream_name: params.stream_name,
+            withdraw_frequency: params.withdraw_frequency,
+            $($field: $value,)*
+        }
+    }};
+}
+
+fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: crate::id(),
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn escrow(metadata: &Pubkey) -> Pubkey {
+    find_escrow_account(metadata.as_ref(), &crate::id()).0
+}
+
+fn create_accounts(
+    sender: Pubkey,
+    metadata: Pubkey,
+    mint: Pubkey,
+    recipient: Pubkey,
+    partner: Pubkey,
+) -> crate::accounts::Create {
+    let treasury = STRM_TREASURY.parse().unwrap();
+    crate::accounts::Create {
+        sender,
+        sender_tokens: get_associated_token_address(&sender, &mint),
+        recipient,
+        metadata,
+        escrow_tokens: escrow(&metadata),
+        recipient_tokens: get_associated_token_address(&recipient, &mint),
+        streamflow_treasury: treasury,
+        streamflow_treasury_tokens: get_associated_token_address(&treasury, &mint),
+        withdrawor: WITHDRAWOR_ADDRESS.parse().unwrap(),
+        partner,
+        partner_tokens: get_associated_token_address(&partner, &mint),
+        mint,
+        fee_oracle: FEE_ORACLE_ADDRESS.parse().unwrap(),
+        rent: anchor_lang::solana_program::sysvar::rent::ID,
+        timelock_program: crate::id(),
+        token_program: anchor_spl::token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+        system_program: anchor_lang::system_program::ID,
+    }
+}
+
+fn create_unchecked_accounts(
+    sender: Pubkey,
+    metadata: Pubkey,
+    mint: Pubkey,
+) -> crate::accounts::CreateUnchecked {
+    crate::accounts::CreateUnchecked {
+        sender,
+        sender_tokens: get_associated_token_address(&sender, &mint),
+        metadata,
+        escrow_tokens: escrow(&metadata),
+        withdrawor: WITHDRAWOR_ADDRESS.parse().unwrap(),
+        mint,
+        fee_oracle: FEE_ORACLE_ADDRESS.parse().unwrap(),
+        rent: anchor_lang::solana_program::sysvar::rent::ID,
+        timelock_program: crate::id(),
+        token_program: anchor_spl::token::ID,
+        system_program: anchor_lang::system_program::ID,
+    }
+}
+
+fn create_unchecked_with_payer_accounts(
+    payer: Pubkey,
+    sender: Pubkey,
+    metadata: Pubkey,
+    mint: Pubkey,
+) -> crate::accounts::CreateUncheckedWithPayer {
+    crate::accounts::CreateUncheckedWithPayer {
+        payer,
+        sender,
+        sender_tokens: get_associated_token_address(&sender, &mint),
+        metadata,
+        escrow_tokens: escrow(&metadata),
+        withdrawor: WITHDRAWOR_ADDRESS.parse().unwrap(),
+        mint,
+        fee_oracle: FEE_ORACLE_ADDRESS.parse().unwrap(),
+        rent: anchor_lang::solana_program::sysvar::rent::ID,
+        timelock_program: crate::id(),
+        token_program: anchor_spl::token::ID,
+        system_program: anchor_lang::system_program::ID,
+    }
+}
+
+/// Build `create` instruction, `metadata` is a new account that should sign the transaction
+pub fn create(
+    sender: Pubkey,
+    metadata: Pubkey,
+    mint: Pubkey,
+    recipient: Pubkey,
+    partner: Pubkey,
+    params: &CreateParams,
+) -> Instruction {
+    let data = create_data!(Create, params, {
+        pausable: Some(params.pausable),
+        can_update_rate: Some(params.can_update_rate),
+    });
+    instruction(
+        create_accounts(sender, metadata, mint, recipient, partner),
+        data,
+    )
+}
+
+/// Build `create_v2` instruction, metadata PDA is derived from `sender` and `params.nonce`
+pub fn create_v2(
+    sender: Pubkey,
+    mint: Pubkey,
+    recipient: Pubkey,
+    partner: Pubkey,
+    params: &CreateParams,
+) -> Instruction {
+    let (metadata, _) = derive_metadata(&mint, &sender, params.nonce, &crate::id());
+    let data = create_data!(CreateV2, params, {
+        pausable: params.pausable,
+        can_update_rate: params.can_update_rate,
+        nonce: params.nonce,
+    });
+    instruction(
+        create_accounts(sender, metadata, mint, recipient, partner),
+        data,
+    )
+}
+
+/// Build `create_unchecked` instruction, `metadata` should be already allocated with
+/// `METADATA_LEN` bytes and owned by the Streamflow program
+pub fn create_unchecked(
+    sender: Pubkey,
+    metadata: Pubkey,
+    mint: Pubkey,
+    recipient: Pubkey,
+    partner: Pubkey,
+    params: &CreateParams,
+) -> Instruction {
+    let data = create_data!(CreateUnchecked, params, {
+        recipient: recipient,
+        partner: partner,
+        pausable: params.pausable,
+        can_update_rate: params.can_update_rate,
+    });
+    instruction(create_unchecked_accounts(sender, metadata, mint), data)
+}
+
+/// Build `create_unchecked_v2` instruction.
+///
+/// Metadata PDA is derived from `sender` and `params.nonce`, the escrow from the metadata.
+/// `sender` should sign the transaction and hold `params.net_amount_deposited` of `mint` in its
+/// associated token account.
+pub fn create_unchecked_v2(
+    sender: Pubkey,
+    mint: Pubkey,
+    recipient: Pubkey,
+    partner: Pubkey,
+    params: &CreateParams,
+) -> Instruction {
+    let (metadata, _) = derive_metadata(&mint, &sender, params.nonce, &crate::id());
+    let data = create_data!(CreateUncheckedV2, params, {
+        recipient: recipient,
+        partner: partner,
+        pausable: params.pausable,
+        can_update_rate: params.can_update_rate,
+        nonce: params.nonce,
+    });
+    instruction(create_unchecked_accounts(sender, metadata, mint), data)
+}
+
+/// Build `create_unchecked_with_payer` instruction, `metadata` should be already allocated with
+/// `METADATA_LEN` bytes and owned by the Streamflow program
+pub fn create_unchecked_with_payer(
+    payer: Pubkey,
+    sender: Pubkey,
+    metadata: Pubkey,
+    mint: Pubkey,
+    recipient: Pubkey,
+    partner: Pubkey,
+    params: &CreateParams,
+) -> Instruction {
+    let data = create_data!(CreateUncheckedWithPayer, params, {
+        recipient: recipient,
+        partner: partner,
+        pausable: params.pausable,
+        can_update_rate: params.can_update_rate,
+    });
+    instruction(
+        create_unchecked_with_payer_accounts(payer, sender, metadata, mint),
+        data,
+    )
+}
+
+/// Build `create_unchecked_with_payer_v2` instruction, metadata PDA is derived from `payer` and
+/// `params.nonce`
+pub fn create_unchecked_with_payer_v2(
+    payer: Pubkey,
+    sender: Pubkey,
+    mint: Pubkey,
+    recipient: Pubkey,
+    partner: Pubkey,
+    params: &CreateParams,
+) -> Instruction {
+    let (metadata, _) = derive_metadata(&mint, &payer, params.nonce, &crate::id());
+    let data = create_data!(CreateUncheckedWithPayerV2, params, {
+        recipient: recipient,
+        partner: partner,
+        pausable: params.pausable,
+        can_update_rate: params.can_update_rate,
+        nonce: params.nonce,
+    });
+    instruction(
+        create_unchecked_with_payer_accounts(payer, sender, metadata, mint),
+        data,
+    )
+}
+
+/// Build `withdraw` instruction, use `u64::MAX` as `amount` to withdraw everything available
+pub fn withdraw(
+    authority: Pubkey,
+    metadata: Pubkey,
+    contract: &Contract,
+    amount: u64,
+) -> Instruction {
+    let accounts = crate::accounts::Withdraw {
+        authority,
+        recipient: contract.recipient,
+        recipient_tokens: contract.recipient_tokens,
+        metadata,
+        escrow_tokens: contract.escrow_tokens,
+        streamflow_treasury: contract.streamflow_treasury,
+        streamflow_treasury_tokens: contract.streamflow_treasury_tokens,
+        partner: contract.partner,
+        partner_tokens: contract.partner_tokens,
+        mint: contract.mint,
+        token_program: anchor_spl::token::ID,
+    };
+    instruction(accounts, crate::instruction::Withdraw { amount })
+}
+
+/// Build `cancel` instruction
+pub fn cancel(authority: Pubkey, metadata: Pubkey, contract: &Contract) -> Instruction {
+    let accounts = crate::accounts::Cancel {
+        authority,
+        sender: contract.sender,
+        sender_tokens: contract.sender_tokens,
+        recipient: contract.recipient,
+        recipient_tokens: contract.recipient_tokens,
+        metadata,
+        escrow_tokens: contract.escrow_tokens,
+        streamflow_treasury: contract.streamflow_treasury,
+        streamflow_treasury_tokens: contract.streamflow_treasury_tokens,
+        partner: contract.partner,
+        partner_tokens: contract.partner_tokens,
+        mint: contract.mint,
+        token_program: anchor_spl::token::ID,
+    };
+    instruction(accounts, crate::instruction::Cancel {})
+}
+
+/// Build `pause` instruction
+pub fn pause(sender: Pubkey, metadata: Pubkey) -> Instruction {
+    instruction(
+        crate::accounts::Pause { sender, metadata },
+        crate::instruction::Pause {},
+    )
+}
+
+/// Build `unpause` instruction
+pub fn unpause(sender: Pubkey, metadata: Pubkey) -> Instruction {
+    instruction(
+        crate::accounts::UnPause { sender, metadata },
+        crate::instruction::Unpause {},
+    )
+}
+
+/// Build `transfer_recipient` instruction
+pub fn transfer_recipient(
+    authority: Pubkey,
+    metadata: Pubkey,
+    mint: Pubkey,
+    new_recipient: Pubkey,
+) -> Instruction {
+    let accounts = crate::accounts::Transfer {
+        authority,
+        new_recipient,
+        new_recipient_tokens: get_associated_token_address(&new_recipient, &mint),
+        metadata,
+        mint,
+        rent: anchor_lang::solana_program::sysvar::rent::ID,
+        token_program: anchor_spl::token::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+        system_program: anchor_lang::system_program::ID,
+    };
+    instruction(accounts, crate::instruction::TransferRecipient {})
+}
+
+/// Build `transfer_sender` instruction, both `sender` and `new_sender` should sign the transaction
+pub fn transfer_sender(
+    sender: Pubkey,
+    new_sender: Pubkey,
+    metadata: Pubkey,
+    mint: Pubkey,
+) -> Instruction {
+    let accounts = crate::accounts::TransferSender {
+        sender,
+        new_sender,
+        new_sender_tokens: get_associated_token_address(&new_sender, &mint),
+        metadata,
+        mint,
+        token_program: anchor_spl::token::ID,
+    };
+    instruction(accounts, crate::instruction::TransferSender {})
+}
+
+/// Build `topup` instruction, tokens are taken from the associated token account of `sender`
+pub fn topup(sender: Pubkey, metadata: Pubkey, contract: &Contract, amount: u64) -> Instruction {
+    let accounts = crate::accounts::Topup {
+        sender,
+        sender_tokens: get_associated_token_address(&sender, &contract.mint),
+        metadata,
+        escrow_tokens: contract.escrow_tokens,
+        streamflow_treasury: contract.streamflow_treasury,
+        streamflow_treasury_tokens: contract.streamflow_treasury_tokens,
+        withdrawor: WITHDRAWOR_ADDRESS.parse().unwrap(),
+        partner: contract.partner,
+        partner_tokens: contract.partner_tokens,
+        mint: contract.mint,
+        token_program: anchor_spl::token::ID,
+        system_program: anchor_lang::system_program::ID,
+    };
+    instruction(accounts, crate::instruction::Topup { amount })
+}
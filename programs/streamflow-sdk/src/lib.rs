@@ -1,5 +1,28 @@
 #![allow(unexpected_cfgs)]
-pub mod state;
+#![allow(clippy::too_many_arguments)]
+pub mod account;
+#[cfg(not(target_os = "solana"))]
+pub mod batch;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod compute_budget;
+pub mod constraints;
+#[cfg(not(target_os = "solana"))]
+pub mod decode;
+pub mod instructions;
+#[cfg(not(target_os = "solana"))]
+pub mod lookup_table;
+pub mod nonce;
+#[cfg(feature = "client")]
+pub mod rpc;
+pub mod update;
+
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub use streamflow_sdk_core::serde_helpers;
+pub use streamflow_sdk_core::{
+    cancel, portfolio, report, schedule, state, timeline, versioned, vesting, view, withdraw,
+};
 
 use anchor_lang::prelude::*;
 
@@ -13,6 +36,20 @@ declare_id!("HqDGZjaVRXJ9MGRQEw7qDc2rAr6iH1n1kAQdCZaCMfMZ");
 #[cfg(not(feature = "devnet"))]
 declare_id!("strmRqUCoQUgGUan5YhzUZa6KqdzwX5L6FpUxfmKg5m");
 
+/// Streamflow program, validates the program account in `Accounts` structs:
+///
+/// ```
+/// # use anchor_lang::prelude::*;
+/// # #[derive(Accounts)]
+/// # pub struct Invoke<'info> {
+/// pub streamflow_program: Program<'info, streamflow_sdk::Streamflow>,
+/// # }
+/// # fn main() {}
+/// ```
+///
+/// Its id follows the `devnet` feature like `streamflow_sdk::id()`.
+pub type Streamflow = program::StreamflowSdk;
+
 /// Streamflow sdk module defining anchor account structs expected from the Streamflow protocol
 /// as well as anchor cpi module used for invoking Streamflow protocol
 ///
@@ -30,17 +67,16 @@ declare_id!("strmRqUCoQUgGUan5YhzUZa6KqdzwX5L6FpUxfmKg5m");
 /// streamflow_sdk = {version = "0.7", features = ["cpi", "devnet"]}
 /// ```
 ///
-/// Example anchor program invoking streamflow create instruction
+/// Example anchor program invoking streamflow create instruction, checked with `cpi` feature
 ///
-///
-/// ```rust
+#[cfg_attr(feature = "cpi", doc = "```rust")]
+#[cfg_attr(not(feature = "cpi"), doc = "```ignore")]
 /// use anchor_lang::prelude::*;
 /// use anchor_spl::{
 ///     associated_token::AssociatedToken,
 ///     token::{Mint, Token, TokenAccount},
 /// };
 ///
-/// use streamflow_sdk;
 /// use streamflow_sdk::cpi::accounts::{
 ///     Create as CpiCreate,
 ///     CreateUnchecked as CpiCreateUnchecked,
@@ -192,6 +228,7 @@ declare_id!("strmRqUCoQUgGUan5YhzUZa6KqdzwX5L6FpUxfmKg5m");
 ///     pub associated_token_program: Program<'info, AssociatedToken>,
 ///     pub system_program: Program<'info, System>,
 /// }
+/// # fn main() {}
 /// ```
 
 
@@ -319,7 +356,6 @@ pub mod streamflow_sdk {
     /// * `withdraw_frequency` - if `automatic_withdrawal` is on, every `withdraw_frequency` seconds **all unlocked** tokens will be sent to the recipient
     /// * `pausable` - Whether Stream can be paused by Sender
     /// * `can_update_rate` - Whether Sender can update `amount_per_period` value of the Stream via `update` method
-
     #[allow(unused_variables)]
     pub fn create_unchecked(
         ctx: Context<CreateUnchecked>,
@@ -0,0 +1,131 @@
+//! Address lookup tables and v0 messages for transactions with Streamflow instructions
+//!
+//! Most accounts of Streamflow instructions are the same for every Stream of a cluster or a mint,
+//! storing them in an address lookup table saves 31 bytes per account in a v0 transaction.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::associated_token::get_associated_token_address;
+use solana_hash::Hash;
+use solana_message::{v0, AddressLookupTableAccount, CompileError, VersionedMessage};
+
+use crate::batch::{compact_len, signed_message_size, transaction_size};
+use crate::state::{Cluster, FEE_ORACLE, STREAMFLOW_TREASURY, WITHDRAWOR};
+
+/// Accounts of a Streamflow deployment shared by all Streams
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProtocolAccounts {
+    pub program_id: Pubkey,
+    pub treasury: Pubkey,
+    pub withdrawor: Pubkey,
+    pub fee_oracle: Pubkey,
+}
+
+impl ProtocolAccounts {
+    /// Accounts of the deployment on `cluster`, deployments differ only in the program id
+    pub fn new(cluster: Cluster) -> Self {
+        Self {
+            program_id: cluster.program_id(),
+            treasury: STREAMFLOW_TREASURY,
+            withdrawor: WITHDRAWOR,
+            fee_oracle: FEE_ORACLE,
+        }
+    }
+
+    /// Accounts of the mainnet deployment, the ones `instructions` builders use by default
+    pub fn mainnet() -> Self {
+        Self::new(Cluster::Mainnet)
+    }
+
+    /// Accounts of the devnet deployment, the ones `instructions` builders use with `devnet`
+    /// feature
+    pub fn devnet() -> Self {
+        Self::new(Cluster::Devnet)
+    }
+}
+
+/// Accounts used by Streamflow instructions regardless of the mint
+pub fn cluster_addresses(protocol: &ProtocolAccounts) -> Vec<Pubkey> {
+    vec![
+        protocol.program_id,
+        protocol.treasury,
+        protocol.withdrawor,
+        protocol.fee_oracle,
+        anchor_spl::token::ID,
+        anchor_spl::associated_token::ID,
+        anchor_lang::system_program::ID,
+        anchor_lang::solana_program::sysvar::rent::ID,
+    ]
+}
+
+/// Accounts used by Streamflow instructions for Streams of `mint`
+pub fn mint_addresses(protocol: &ProtocolAccounts, mint: &Pubkey) -> Vec<Pubkey> {
+    vec![
+        *mint,
+        get_associated_token_address(&protocol.treasury, mint),
+    ]
+}
+
+/// Deduplicated accounts to store in a lookup table for Streams of `mints`
+pub fn lookup_table_addresses(protocol: &ProtocolAccounts, mints: &[Pubkey]) -> Vec<Pubkey> {
+    let mut addresses = cluster_addresses(protocol);
+    for mint in mints {
+        for address in mint_addresses(protocol, mint) {
+            if !addresses.contains(&address) {
+                addresses.push(address);
+            }
+        }
+    }
+    addresses
+}
+
+/// Lookup table account at `key` as if it stored `lookup_table_addresses(protocol, mints)`.
+///
+/// Order of the addresses must match the order they were added to the table on chain.
+pub fn lookup_table_account(
+    key: Pubkey,
+    protocol: &ProtocolAccounts,
+    mints: &[Pubkey],
+) -> AddressLookupTableAccount {
+    AddressLookupTableAccount {
+        key,
+        addresses: lookup_table_addresses(protocol, mints),
+    }
+}
+
+/// Compile `instructions` into a v0 message loading accounts from `lookup_tables`
+pub fn compile_v0_message(
+    payer: &Pubkey,
+    instructions: &[Instruction],
+    lookup_tables: &[AddressLookupTableAccount],
+    recent_blockhash: Hash,
+) -> std::result::Result<VersionedMessage, CompileError> {
+    let message = v0::Message::try_compile(payer, instructions, lookup_tables, recent_blockhash)?;
+    Ok(VersionedMessage::V0(message))
+}
+
+/// Size of a signed transaction carrying `message`
+pub fn versioned_transaction_size(message: &VersionedMessage) -> usize {
+    let message = match message {
+        VersionedMessage::Legacy(message) => return transaction_size(message),
+        VersionedMessage::V0(message) => message,
+    };
+    let lookups: usize = message
+        .address_table_lookups
+        .iter()
+        .map(|lookup| {
+            32 + compact_len(lookup.writable_indexes.len())
+                + lookup.writable_indexes.len()
+                + compact_len(lookup.readonly_indexes.len())
+                + lookup.readonly_indexes.len()
+        })
+        .sum();
+    // version prefix and address table lookups follow the legacy layout
+    signed_message_size(
+        message.header.num_required_signatures as usize,
+        message.account_keys.len(),
+        &message.instructions,
+    ) + 1
+        + compact_len(message.address_table_lookups.len())
+        + lookups
+}
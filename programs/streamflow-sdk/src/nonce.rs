@@ -0,0 +1,129 @@
+//! Allocation of nonces for PDA Streams created with v2 instructions
+//!
+//! Metadata of such Streams is derived with `derive_metadata(mint, payer, nonce, pid)`, so every
+//! nonce can be used only once per mint and payer.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use anchor_lang::prelude::*;
+
+use crate::state::derive_metadata;
+
+/// Prefix hashed together with an external id in `external_id_nonce`
+pub const EXTERNAL_ID_NONCE_PREFIX: &[u8] = b"strm-nonce";
+/// Default number of nonces `find_free_nonce` checks, every check derives a PDA
+pub const DEFAULT_NONCE_ATTEMPTS: u32 = 100;
+
+/// Nonce together with the metadata PDA it derives
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AllocatedNonce {
+    pub nonce: u32,
+    pub metadata: Pubkey,
+    pub bump: u8,
+}
+
+impl AllocatedNonce {
+    pub fn new(mint: &Pubkey, payer: &Pubkey, nonce: u32, pid: &Pubkey) -> Self {
+        let (metadata, bump) = derive_metadata(mint, payer, nonce, pid);
+        Self {
+            nonce,
+            metadata,
+            bump,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NonceError {
+    /// Every checked nonce starting from the requested one is in use
+    Exhausted,
+    /// Metadata derived from the nonce of an external id is already in use
+    Collision { nonce: u32, metadata: Pubkey },
+}
+
+impl fmt::Display for NonceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NonceError::Exhausted => f.write_str("no free nonce left"),
+            NonceError::Collision { nonce, metadata } => {
+                write!(f, "nonce {nonce} is already used by metadata {metadata}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for NonceError {}
+
+/// Find the first nonce from `start` whose metadata account isn't in use, checking at most
+/// `max_attempts` nonces.
+///
+/// `in_use` is called with derived metadata addresses, e.g. to check whether the account exists
+/// on chain. Bound `max_attempts` by the compute units or RPC requests you can spend, see
+/// `DEFAULT_NONCE_ATTEMPTS`.
+pub fn find_free_nonce(
+    mint: &Pubkey,
+    payer: &Pubkey,
+    pid: &Pubkey,
+    start: u32,
+    max_attempts: u32,
+    mut in_use: impl FnMut(&Pubkey) -> bool,
+) -> std::result::Result<AllocatedNonce, NonceError> {
+    (start..=u32::MAX)
+        .take(max_attempts as usize)
+        .map(|nonce| AllocatedNonce::new(mint, payer, nonce, pid))
+        .find(|allocated| !in_use(&allocated.metadata))
+        .ok_or(NonceError::Exhausted)
+}
+
+/// Find the first nonce from `start` that doesn't derive any of `existing` metadata accounts
+pub fn next_unused_nonce<'a>(
+    mint: &Pubkey,
+    payer: &Pubkey,
+    pid: &Pubkey,
+    start: u32,
+    existing: impl IntoIterator<Item = &'a Pubkey>,
+) -> std::result::Result<AllocatedNonce, NonceError> {
+    let existing: HashSet<&Pubkey> = existing.into_iter().collect();
+    // one of the next `existing.len() + 1` nonces is free unless the `u32` range ends
+    let max_attempts = u32::try_from(existing.len()).map_or(u32::MAX, |len| len.saturating_add(1));
+    find_free_nonce(mint, payer, pid, start, max_attempts, |metadata| {
+        existing.contains(metadata)
+    })
+}
+
+/// Deterministic nonce of an external id, e.g. an invoice or employee id.
+///
+/// First 4 bytes of `sha256(EXTERNAL_ID_NONCE_PREFIX || external_id)`, uses the sha256 syscall
+/// on chain. Different ids may map to the same nonce, check it with `allocate_external_id_nonce`.
+pub fn external_id_nonce(external_id: &[u8]) -> u32 {
+    let hash = solana_sha256_hasher::hashv(&[EXTERNAL_ID_NONCE_PREFIX, external_id]);
+    u32::from_be_bytes(hash.to_bytes()[..4].try_into().unwrap())
+}
+
+/// Nonce of an external id with its metadata, fails if the metadata account is already in use.
+///
+/// The metadata may be in use because a Stream for this id was already created or because of a
+/// collision with another id, callers can tell those apart by reading the existing Stream.
+pub fn allocate_external_id_nonce(
+    mint: &Pubkey,
+    payer: &Pubkey,
+    pid: &Pubkey,
+    external_id: &[u8],
+    in_use: impl FnOnce(&Pubkey) -> bool,
+) -> std::result::Result<AllocatedNonce, NonceError> {
+    let allocated = AllocatedNonce::new(mint, payer, external_id_nonce(external_id), pid);
+    if in_use(&allocated.metadata) {
+        return Err(NonceError::Collision {
+            nonce: allocated.nonce,
+            metadata: allocated.metadata,
+        });
+    }
+    Ok(allocated)
+}
+
+/// Whether a metadata account passed to a program is already created or funded,
+/// to be used as `in_use` on chain
+pub fn metadata_in_use(metadata: &AccountInfo) -> bool {
+    metadata.lamports() > 0 || !metadata.data_is_empty()
+}
@@ -0,0 +1,213 @@
+//! `StreamflowRpc` over the JSON-RPC HTTP API of a Solana node
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! use streamflow_sdk::client::StreamflowClient;
+//! use streamflow_sdk::rpc::HttpRpc;
+//!
+//! let client = StreamflowClient::new(HttpRpc::new("https://api.mainnet-beta.solana.com"));
+//! let stream = client.get_stream(&"<METADATA>".parse()?).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use anchor_lang::prelude::Pubkey;
+use async_trait::async_trait;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde_json::{json, Value};
+use solana_hash::Hash;
+use solana_signature::Signature;
+use solana_transaction::Transaction;
+
+use crate::client::{Account, MemcmpFilter, RpcError, StreamflowRpc};
+
+/// Commitment `HttpRpc::new` reads with and runs preflight checks at
+pub const DEFAULT_COMMITMENT: &str = "confirmed";
+
+/// JSON-RPC client of a Solana node
+#[derive(Clone, Debug)]
+pub struct HttpRpc {
+    http: reqwest::Client,
+    url: String,
+    commitment: String,
+}
+
+impl HttpRpc {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self::with_commitment(url, DEFAULT_COMMITMENT)
+    }
+
+    /// Client using `commitment`, one of `processed`, `confirmed` or `finalized`
+    pub fn with_commitment(url: impl Into<String>, commitment: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url: url.into(),
+            commitment: commitment.into(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Send a request and return its `result`, JSON-RPC errors are returned as `RpcError`
+    async fn call(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let mut response: Value = self
+            .http
+            .post(&self.url)
+            .json(&request)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("{method}: {error}").into());
+        }
+        match response.get_mut("result") {
+            Some(result) => Ok(result.take()),
+            None => Err(format!("{method}: response without result").into()),
+        }
+    }
+}
+
+fn field<'a>(value: &'a Value, name: &str) -> Result<&'a Value, RpcError> {
+    value
+        .get(name)
+        .ok_or_else(|| format!("missing `{name}` in {value}").into())
+}
+
+fn as_u64(value: &Value, name: &str) -> Result<u64, RpcError> {
+    field(value, name)?
+        .as_u64()
+        .ok_or_else(|| format!("`{name}` isn't an integer").into())
+}
+
+fn as_str<'a>(value: &'a Value, name: &str) -> Result<&'a str, RpcError> {
+    field(value, name)?
+        .as_str()
+        .ok_or_else(|| format!("`{name}` isn't a string").into())
+}
+
+/// Account in `base64` encoding as returned by `getAccountInfo` and `getProgramAccounts`
+fn decode_account(value: &Value) -> Result<Account, RpcError> {
+    let data = field(value, "data")?
+        .get(0)
+        .and_then(Value::as_str)
+        .ok_or("`data` isn't base64 encoded")?;
+    Ok(Account {
+        lamports: as_u64(value, "lamports")?,
+        data: BASE64_STANDARD.decode(data)?,
+        owner: as_str(value, "owner")?.parse()?,
+        executable: field(value, "executable")?.as_bool().unwrap_or(false),
+        rent_epoch: as_u64(value, "rentEpoch")?,
+    })
+}
+
+fn encode_transaction(transaction: &Transaction) -> Result<String, RpcError> {
+    Ok(BASE64_STANDARD.encode(bincode::serialize(transaction)?))
+}
+
+#[async_trait]
+impl StreamflowRpc for HttpRpc {
+    async fn get_account(&self, key: &Pubkey) -> Result<Option<Account>, RpcError> {
+        let config = json!({ "encoding": "base64", "commitment": self.commitment });
+        let result = self
+            .call("getAccountInfo", json!([key.to_string(), config]))
+            .await?;
+        match field(&result, "value")? {
+            Value::Null => Ok(None),
+            account => decode_account(account).map(Some),
+        }
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        data_size: u64,
+        filters: &[MemcmpFilter],
+    ) -> Result<Vec<(Pubkey, Account)>, RpcError> {
+        let mut rpc_filters = vec![json!({ "dataSize": data_size })];
+        rpc_filters.extend(filters.iter().map(|filter| {
+            json!({ "memcmp": {
+                "offset": filter.offset,
+                "bytes": BASE64_STANDARD.encode(&filter.bytes),
+                "encoding": "base64",
+            } })
+        }));
+        let config = json!({
+            "encoding": "base64",
+            "commitment": self.commitment,
+            "filters": rpc_filters,
+        });
+        let result = self
+            .call(
+                "getProgramAccounts",
+                json!([program_id.to_string(), config]),
+            )
+            .await?;
+        result
+            .as_array()
+            .ok_or("`getProgramAccounts` result isn't a list")?
+            .iter()
+            .map(|item| {
+                let key = as_str(item, "pubkey")?.parse()?;
+                Ok((key, decode_account(field(item, "account")?)?))
+            })
+            .collect()
+    }
+
+    async fn get_latest_blockhash(&self) -> Result<Hash, RpcError> {
+        let config = json!({ "commitment": self.commitment });
+        let result = self.call("getLatestBlockhash", json!([config])).await?;
+        Ok(as_str(field(&result, "value")?, "blockhash")?.parse()?)
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(
+        &self,
+        data_len: usize,
+    ) -> Result<u64, RpcError> {
+        let config = json!({ "commitment": self.commitment });
+        let result = self
+            .call(
+                "getMinimumBalanceForRentExemption",
+                json!([data_len, config]),
+            )
+            .await?;
+        result
+            .as_u64()
+            .ok_or_else(|| "rent exemption isn't an integer".into())
+    }
+
+    async fn send_transaction(&self, transaction: &Transaction) -> Result<Signature, RpcError> {
+        let config = json!({ "encoding": "base64", "preflightCommitment": self.commitment });
+        let result = self
+            .call(
+                "sendTransaction",
+                json!([encode_transaction(transaction)?, config]),
+            )
+            .await?;
+        Ok(result.as_str().ok_or("signature isn't a string")?.parse()?)
+    }
+
+    async fn simulate_compute_units(&self, transaction: &Transaction) -> Result<u64, RpcError> {
+        let config = json!({
+            "encoding": "base64",
+            "commitment": self.commitment,
+            "sigVerify": false,
+            "replaceRecentBlockhash": true,
+        });
+        let result = self
+            .call(
+                "simulateTransaction",
+                json!([encode_transaction(transaction)?, config]),
+            )
+            .await?;
+        let value = field(&result, "value")?;
+        match value.get("err") {
+            None | Some(Value::Null) => as_u64(value, "unitsConsumed"),
+            Some(err) => Err(format!("simulation failed: {err}").into()),
+        }
+    }
+}
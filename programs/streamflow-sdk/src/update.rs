@@ -0,0 +1,74 @@
+//! `update` instruction built from `UpdateArgs`
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::InstructionData;
+
+pub use streamflow_sdk_core::update::*;
+
+use crate::state::WITHDRAWOR_ADDRESS;
+
+/// Build `update` instruction for a Stream, `sender` should sign the transaction
+///
+/// ```
+/// use anchor_lang::prelude::Pubkey;
+/// use streamflow_sdk::update::{update_instruction, UpdateArgs};
+///
+/// let args = UpdateArgs::new()
+///     .enable_automatic_withdrawal(3600)
+///     .disable_sender_cancel();
+/// let ix = update_instruction(Pubkey::new_unique(), Pubkey::new_unique(), args).unwrap();
+/// assert_eq!(ix.program_id, streamflow_sdk::id());
+/// ```
+pub fn update_instruction(
+    sender: Pubkey,
+    metadata: Pubkey,
+    args: UpdateArgs,
+) -> std::result::Result<Instruction, UpdateError> {
+    args.validate()?;
+    let accounts = crate::accounts::Update {
+        sender,
+        metadata,
+        withdrawor: WITHDRAWOR_ADDRESS.parse().unwrap(),
+        system_program: anchor_lang::system_program::ID,
+    };
+    Ok(Instruction {
+        program_id: crate::id(),
+        accounts: accounts.to_account_metas(None),
+        data: crate::instruction::Update::from(args).data(),
+    })
+}
+
+/// Invoke `update` instruction of the Streamflow protocol after validating `args`
+#[cfg(feature = "cpi")]
+pub fn update_cpi<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, crate::cpi::accounts::Update<'info>>,
+    args: UpdateArgs,
+) -> Result<()> {
+    if let Err(e) = args.validate() {
+        msg!("Invalid update arguments: {}", e);
+        return Err(ProgramError::InvalidArgument.into());
+    }
+    crate::cpi::update(
+        ctx,
+        args.enable_automatic_withdrawal,
+        args.withdraw_frequency,
+        args.amount_per_period,
+        args.transferable_by_sender,
+        args.transferable_by_recipient,
+        args.cancelable_by_sender,
+    )
+}
+
+impl From<UpdateArgs> for crate::instruction::Update {
+    fn from(args: UpdateArgs) -> Self {
+        Self {
+            enable_automatic_withdrawal: args.enable_automatic_withdrawal,
+            withdraw_frequency: args.withdraw_frequency,
+            amount_per_period: args.amount_per_period,
+            transferable_by_sender: args.transferable_by_sender,
+            transferable_by_recipient: args.transferable_by_recipient,
+            cancelable_by_sender: args.cancelable_by_sender,
+        }
+    }
+}
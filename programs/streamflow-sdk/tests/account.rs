@@ -0,0 +1,73 @@
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+use streamflow_sdk::account::StreamMetadata;
+use streamflow_sdk::state::METADATA_LEN;
+use streamflow_sdk::Streamflow;
+
+const METADATA_V3: &[u8] = include_bytes!("fixtures/metadata_v3.bin");
+
+fn load(owner: &Pubkey, data: &[u8]) -> Result<StreamMetadata> {
+    let key = Pubkey::new_unique();
+    let mut lamports = 1;
+    let mut data = data.to_vec();
+    let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, owner, false, 0);
+    Account::<StreamMetadata>::try_from(&info).map(|account| account.into_inner())
+}
+
+fn error_code(result: Result<StreamMetadata>) -> u32 {
+    match result.unwrap_err() {
+        Error::AnchorError(e) => e.error_code_number,
+        e => panic!("unexpected error {e}"),
+    }
+}
+
+#[test]
+fn loads_stream() {
+    let metadata = load(&streamflow_sdk::id(), METADATA_V3).unwrap();
+    let contract = metadata.into_inner();
+    assert_eq!(contract.version, 3);
+    assert_eq!(contract.ix.net_amount_deposited, 1_000_000_000);
+}
+
+#[test]
+fn checks_owner_and_magic() {
+    assert_eq!(
+        error_code(load(&Pubkey::new_unique(), METADATA_V3)),
+        u32::from(ErrorCode::AccountOwnedByWrongProgram)
+    );
+    assert_eq!(
+        error_code(load(&streamflow_sdk::id(), &[0; METADATA_LEN])),
+        u32::from(ErrorCode::AccountNotInitialized)
+    );
+    assert_eq!(
+        error_code(load(&streamflow_sdk::id(), &METADATA_V3[..100])),
+        u32::from(ErrorCode::AccountDidNotDeserialize)
+    );
+}
+
+#[test]
+fn checks_program_id() {
+    let owner = Pubkey::default();
+    let check = |key: Pubkey| {
+        let mut lamports = 1;
+        let mut data = vec![];
+        let info = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            true,
+            0,
+        );
+        Program::<Streamflow>::try_from(&info).map(|program| program.key())
+    };
+    assert_eq!(check(streamflow_sdk::id()).unwrap(), streamflow_sdk::id());
+    match check(Pubkey::new_unique()).unwrap_err() {
+        Error::AnchorError(e) => {
+            assert_eq!(e.error_code_number, u32::from(ErrorCode::InvalidProgramId))
+        }
+        e => panic!("unexpected error {e}"),
+    }
+}
@@ -0,0 +1,222 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use solana_message::Message;
+use solana_transaction::Transaction;
+use streamflow_sdk::batch::{
+    transaction_size, BatchEntry, BatchError, BatchPlanner, StreamOptions, StreamSchedule,
+    LAMPORTS_PER_SIGNATURE, MAX_TRANSACTION_SIZE,
+};
+use streamflow_sdk::compute_budget::{set_compute_unit_limit, set_compute_unit_price};
+use streamflow_sdk::decode::decode_instruction;
+use streamflow_sdk::nonce::AllocatedNonce;
+use streamflow_sdk::state::{calculate_fee_from_amount, find_escrow_account};
+
+const START: u64 = 1_700_000_000;
+
+fn entry(amount: u64) -> BatchEntry {
+    BatchEntry {
+        recipient: Pubkey::new_unique(),
+        amount,
+        schedule: StreamSchedule {
+            start_time: START,
+            period: 86_400,
+            amount_per_period: amount / 100 + 1,
+            cliff: 0,
+            cliff_amount: 0,
+        },
+        stream_name: [0; 64],
+    }
+}
+
+fn planner() -> BatchPlanner {
+    BatchPlanner::new(
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        StreamOptions::default(),
+        Rent::default(),
+        80_000,
+    )
+}
+
+fn serialized_size(instructions: &[Instruction], payer: &Pubkey) -> usize {
+    let tx = Transaction::new_unsigned(Message::new(instructions, Some(payer)));
+    bincode::serialize(&tx).unwrap().len()
+}
+
+#[test]
+fn transaction_size_matches_serialization() {
+    let planner = planner();
+    let plan = planner.plan(&[entry(1_000), entry(2_000)]).unwrap();
+    let creates = &plan.transactions[0].instructions;
+    for instructions in [
+        &creates[..1],
+        &creates[..],
+        &[set_compute_unit_price(1)][..],
+    ] {
+        let message = Message::new(instructions, Some(&planner.sender));
+        assert_eq!(
+            transaction_size(&message),
+            serialized_size(instructions, &planner.sender)
+        );
+    }
+}
+
+#[test]
+fn splits_into_transactions() {
+    let mut planner = planner();
+    planner.first_nonce = 5;
+    planner.compute_unit_price = Some(10_000);
+    let entries: Vec<_> = (1..=10).map(|i| entry(i * 1_000_000)).collect();
+    let plan = planner.plan(&entries).unwrap();
+
+    assert!(plan.transactions.len() > 1);
+    let mut index = 0;
+    for tx in &plan.transactions {
+        assert!(tx.size <= MAX_TRANSACTION_SIZE);
+        assert_eq!(tx.size, serialized_size(&tx.instructions, &planner.sender));
+        assert_eq!(
+            tx.compute_units,
+            tx.streams.len() as u32 * planner.compute_units_per_create
+        );
+        // the compute budget comes first and covers every create
+        assert_eq!(tx.instructions[0], set_compute_unit_limit(tx.compute_units));
+        assert_eq!(tx.instructions[1], set_compute_unit_price(10_000));
+        assert_eq!(tx.instructions.len(), tx.streams.len() + 2);
+
+        for (ix, stream) in tx.instructions[2..].iter().zip(&tx.streams) {
+            assert_eq!(stream.index, index);
+            assert_eq!(stream.nonce, 5 + index as u32);
+            assert_eq!(stream.recipient, entries[index].recipient);
+            let keys: Vec<_> = ix.accounts.iter().map(|a| a.pubkey).collect();
+            let decoded = decode_instruction(&ix.data, &keys).unwrap();
+            assert_eq!(decoded.metadata(), stream.metadata);
+            let (escrow, _) = find_escrow_account(stream.metadata.as_ref(), &streamflow_sdk::id());
+            assert_eq!(stream.escrow_tokens, escrow);
+            index += 1;
+        }
+    }
+    assert_eq!(index, entries.len());
+    assert_eq!(plan.next_nonce, 5 + entries.len() as u32);
+}
+
+#[test]
+fn respects_compute_limit() {
+    let mut planner = planner();
+    planner.max_compute_units = 2 * planner.compute_units_per_create;
+    let plan = planner.plan(&[entry(1), entry(2), entry(3)]).unwrap();
+    let streams: Vec<_> = plan
+        .transactions
+        .iter()
+        .map(|tx| tx.streams.len())
+        .collect();
+    assert_eq!(streams, [2, 1]);
+
+    planner.max_compute_units = planner.compute_units_per_create - 1;
+    assert_eq!(
+        planner.plan(&[entry(1)]).unwrap_err(),
+        BatchError::InstructionTooLarge
+    );
+    let mut planner = self::planner();
+    planner.max_transaction_size = 300;
+    assert_eq!(
+        planner.plan(&[entry(1)]).unwrap_err(),
+        BatchError::InstructionTooLarge
+    );
+}
+
+#[test]
+fn sums_totals() {
+    let mut planner = planner();
+    planner.creation_fee_lamports = 1_000;
+    let rent = Rent {
+        lamports_per_byte_year: 1,
+        exemption_threshold: 2.0,
+        burn_percent: 0,
+    };
+    planner.rent = rent.clone();
+    let entries = [entry(1_000_000), entry(3_000_000)];
+    let plan = planner.plan(&entries).unwrap();
+
+    assert_eq!(plan.total_deposited, 4_000_000);
+    let fees = calculate_fee_from_amount(1_000_000, planner.streamflow_fee_percent)
+        + calculate_fee_from_amount(3_000_000, planner.streamflow_fee_percent);
+    assert_eq!(plan.total_fees, fees);
+    assert_eq!(plan.total_tokens(), 4_000_000 + fees);
+    // rent of the given cluster rather than the default one
+    let per_stream = rent.minimum_balance(1104) + rent.minimum_balance(165);
+    assert_ne!(
+        per_stream,
+        Rent::default().minimum_balance(1104) + Rent::default().minimum_balance(165)
+    );
+    assert_eq!(plan.rent_lamports, 2 * per_stream);
+    assert_eq!(plan.creation_fee_lamports, 2_000);
+    assert_eq!(plan.signature_lamports, LAMPORTS_PER_SIGNATURE);
+    assert_eq!(
+        plan.total_lamports(),
+        2 * per_stream + 2_000 + LAMPORTS_PER_SIGNATURE
+    );
+}
+
+#[test]
+fn rejects_overflowing_totals() {
+    let planner = planner();
+    assert_eq!(
+        planner.plan(&[entry(u64::MAX), entry(1)]).unwrap_err(),
+        BatchError::Overflow
+    );
+    // deposits fit, deposits and fees together don't
+    assert_eq!(
+        planner.plan(&[entry(u64::MAX - 1)]).unwrap_err(),
+        BatchError::Overflow
+    );
+
+    let mut planner = self::planner();
+    planner.creation_fee_lamports = u64::MAX;
+    assert_eq!(planner.plan(&[entry(1)]).unwrap_err(), BatchError::Overflow);
+}
+
+#[test]
+fn skips_used_nonces() {
+    let planner = planner();
+    let pid = streamflow_sdk::id();
+    let used: Vec<_> = [0, 2]
+        .map(|nonce| AllocatedNonce::new(&planner.mint, &planner.sender, nonce, &pid).metadata)
+        .to_vec();
+    let plan = planner
+        .plan_skipping(&[entry(1), entry(2)], |metadata| used.contains(metadata))
+        .unwrap();
+    let nonces: Vec<_> = plan.transactions[0]
+        .streams
+        .iter()
+        .map(|s| s.nonce)
+        .collect();
+    assert_eq!(nonces, [1, 3]);
+    assert_eq!(plan.next_nonce, 4);
+}
+
+#[test]
+fn rejects_invalid_entries() {
+    let planner = planner();
+    assert_eq!(
+        planner.plan(&[entry(1), entry(0)]).unwrap_err(),
+        BatchError::ZeroAmount(1)
+    );
+    let mut invalid = entry(1_000);
+    invalid.schedule.cliff_amount = 1_001;
+    assert_eq!(
+        planner.plan(&[invalid]).unwrap_err(),
+        BatchError::InvalidSchedule(0)
+    );
+}
+
+#[test]
+fn bounds_nonce_search() {
+    let mut planner = planner();
+    planner.max_nonce_attempts = 3;
+    assert_eq!(
+        planner
+            .plan_skipping(&[entry(1), entry(2)], |_| true)
+            .unwrap_err(),
+        BatchError::NoFreeNonce(0)
+    );
+}
@@ -0,0 +1,344 @@
+#![cfg(feature = "client")]
+
+use std::sync::Mutex;
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use async_trait::async_trait;
+use futures::executor::block_on;
+use solana_hash::Hash;
+use solana_keypair::Keypair;
+use solana_signature::Signature;
+use solana_signer::Signer;
+use solana_system_interface::instruction::create_account;
+use solana_transaction::Transaction;
+use streamflow_sdk::client::{
+    Account, ClientError, MemcmpFilter, RpcError, StreamflowClient, StreamflowRpc,
+};
+use streamflow_sdk::constraints::AccountError;
+use streamflow_sdk::state::{derive_metadata, Contract, CreateParams, METADATA_LEN};
+use streamflow_sdk::view::offset;
+
+const METADATA_V3: &[u8] = include_bytes!("fixtures/metadata_v3.bin");
+/// Compute units `MockRpc` simulates for every instruction
+const UNITS_PER_INSTRUCTION: u64 = 1_000;
+
+/// In-memory accounts, remembers sent transactions
+#[derive(Default)]
+struct MockRpc {
+    accounts: Vec<(Pubkey, Account)>,
+    sent: Mutex<Vec<Transaction>>,
+}
+
+impl MockRpc {
+    fn with_account(mut self, key: Pubkey, owner: Pubkey, data: &[u8]) -> Self {
+        let account = Account {
+            lamports: 1,
+            data: data.to_vec(),
+            owner,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.accounts.push((key, account));
+        self
+    }
+}
+
+#[async_trait]
+impl StreamflowRpc for MockRpc {
+    async fn get_account(&self, key: &Pubkey) -> std::result::Result<Option<Account>, RpcError> {
+        Ok(self
+            .accounts
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, a)| a.clone()))
+    }
+
+    async fn get_program_accounts(
+        &self,
+        program_id: &Pubkey,
+        data_size: u64,
+        filters: &[MemcmpFilter],
+    ) -> std::result::Result<Vec<(Pubkey, Account)>, RpcError> {
+        Ok(self
+            .accounts
+            .iter()
+            .filter(|(_, a)| a.owner == *program_id && a.data.len() as u64 == data_size)
+            .filter(|(_, a)| {
+                filters
+                    .iter()
+                    .all(|f| a.data.get(f.offset..f.offset + f.bytes.len()) == Some(&f.bytes[..]))
+            })
+            .cloned()
+            .collect())
+    }
+
+    async fn get_latest_blockhash(&self) -> std::result::Result<Hash, RpcError> {
+        Ok(Hash::new_from_array([7; 32]))
+    }
+
+    async fn get_minimum_balance_for_rent_exemption(
+        &self,
+        data_len: usize,
+    ) -> std::result::Result<u64, RpcError> {
+        Ok(Rent::default().minimum_balance(data_len))
+    }
+
+    async fn send_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> std::result::Result<Signature, RpcError> {
+        self.sent.lock().unwrap().push(transaction.clone());
+        Ok(transaction.signatures[0])
+    }
+
+    async fn simulate_compute_units(
+        &self,
+        transaction: &Transaction,
+    ) -> std::result::Result<u64, RpcError> {
+        self.sent.lock().unwrap().push(transaction.clone());
+        Ok(transaction.message.instructions.len() as u64 * UNITS_PER_INSTRUCTION)
+    }
+}
+
+#[test]
+fn reads_streams() {
+    let metadata = Pubkey::new_unique();
+    let expected = Contract::deserialize(&mut &METADATA_V3[..]).unwrap();
+    let rpc = MockRpc::default()
+        .with_account(metadata, streamflow_sdk::id(), METADATA_V3)
+        .with_account(Pubkey::new_unique(), Pubkey::new_unique(), METADATA_V3)
+        .with_account(
+            Pubkey::new_unique(),
+            streamflow_sdk::id(),
+            &[0; METADATA_LEN - 1],
+        );
+    let client = StreamflowClient::new(rpc);
+
+    let contract = block_on(client.get_stream(&metadata)).unwrap();
+    assert_eq!(
+        contract.try_to_vec().unwrap(),
+        expected.try_to_vec().unwrap()
+    );
+
+    let by_sender = block_on(client.streams_by_sender(&expected.sender)).unwrap();
+    assert_eq!(by_sender.streams.len(), 1);
+    assert_eq!(by_sender.streams[0].0, metadata);
+    assert!(by_sender.invalid.is_empty());
+    assert_eq!(
+        block_on(client.streams_by_recipient(&expected.recipient))
+            .unwrap()
+            .streams
+            .len(),
+        1
+    );
+    assert!(block_on(client.streams_by_mint(&expected.recipient))
+        .unwrap()
+        .streams
+        .is_empty());
+
+    let missing = Pubkey::new_unique();
+    assert!(matches!(
+        block_on(client.get_stream(&missing)),
+        Err(ClientError::AccountNotFound(key)) if key == missing
+    ));
+}
+
+#[test]
+fn reports_invalid_streams() {
+    let metadata = Pubkey::new_unique();
+    let (zeroed, malformed) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut data = METADATA_V3.to_vec();
+    // `closed` flag isn't a valid bool
+    data[offset::CLOSED] = 2;
+    let rpc = MockRpc::default()
+        .with_account(metadata, streamflow_sdk::id(), METADATA_V3)
+        .with_account(zeroed, streamflow_sdk::id(), &[0; METADATA_LEN])
+        .with_account(malformed, streamflow_sdk::id(), &data);
+    let client = StreamflowClient::new(rpc);
+
+    let list = block_on(client.get_streams(&[])).unwrap();
+    let streams: Vec<_> = list.streams.iter().map(|(key, _)| *key).collect();
+    assert_eq!(streams, [metadata]);
+    assert_eq!(
+        list.invalid,
+        [
+            (zeroed, AccountError::InvalidMetadata),
+            (malformed, AccountError::InvalidMetadata)
+        ]
+    );
+    assert!(matches!(
+        block_on(client.get_stream(&zeroed)),
+        Err(ClientError::InvalidStream(key, AccountError::InvalidMetadata)) if key == zeroed
+    ));
+}
+
+#[test]
+fn rejects_foreign_accounts() {
+    let metadata = Pubkey::new_unique();
+    let rpc = MockRpc::default().with_account(metadata, Pubkey::new_unique(), METADATA_V3);
+    let client = StreamflowClient::new(rpc);
+    assert!(matches!(
+        block_on(client.get_stream(&metadata)),
+        Err(ClientError::InvalidStream(
+            _,
+            AccountError::InvalidMetadataOwner
+        ))
+    ));
+}
+
+#[test]
+fn sends_signed_withdraw() {
+    let metadata = Pubkey::new_unique();
+    let recipient = Keypair::new();
+    let rpc = MockRpc::default().with_account(metadata, streamflow_sdk::id(), METADATA_V3);
+    let client = StreamflowClient::new(rpc);
+
+    let signature = block_on(client.withdraw(&recipient, metadata, u64::MAX)).unwrap();
+
+    let sent = client.rpc().sent.lock().unwrap();
+    assert_eq!(sent.len(), 1);
+    let tx = &sent[0];
+    assert_eq!(tx.signatures, vec![signature]);
+    assert!(tx.is_signed());
+    assert_eq!(tx.message.account_keys[0], recipient.pubkey());
+    let ix = &tx.message.instructions[0];
+    assert_eq!(
+        tx.message.account_keys[ix.program_id_index as usize],
+        streamflow_sdk::id()
+    );
+    assert_eq!(
+        &ix.data[..8],
+        streamflow_sdk::instruction::Withdraw::DISCRIMINATOR
+    );
+    assert_eq!(ix.data[8..], u64::MAX.to_le_bytes());
+}
+
+#[test]
+fn requires_all_signers() {
+    let metadata = Pubkey::new_unique();
+    let rpc = MockRpc::default().with_account(metadata, streamflow_sdk::id(), METADATA_V3);
+    let client = StreamflowClient::new(rpc);
+    let sender = Keypair::new();
+
+    let ix = streamflow_sdk::instructions::transfer_sender(
+        sender.pubkey(),
+        Pubkey::new_unique(),
+        metadata,
+        Pubkey::new_unique(),
+    );
+    assert!(matches!(
+        block_on(client.send(&[ix], &[&sender])),
+        Err(ClientError::Signer(_))
+    ));
+    assert!(client.rpc().sent.lock().unwrap().is_empty());
+}
+
+/// Params of the fixture Stream, its mint and recipient
+fn create_params() -> (CreateParams, Pubkey, Pubkey) {
+    let contract = Contract::deserialize(&mut &METADATA_V3[..]).unwrap();
+    (contract.ix, contract.mint, contract.recipient)
+}
+
+#[test]
+fn allocates_metadata_for_create_unchecked() {
+    let client = StreamflowClient::new(MockRpc::default());
+    let (params, mint, recipient) = create_params();
+    let (payer, sender, metadata) = (Keypair::new(), Keypair::new(), Keypair::new());
+    let partner = Pubkey::new_unique();
+
+    block_on(client.create_unchecked(&sender, &metadata, mint, recipient, partner, &params))
+        .unwrap();
+    block_on(client.create_unchecked_with_payer(
+        &payer, &sender, &metadata, mint, recipient, partner, &params,
+    ))
+    .unwrap();
+
+    let sent = client.rpc().sent.lock().unwrap();
+    let expected = [
+        (
+            sender.pubkey(),
+            streamflow_sdk::instructions::create_unchecked(
+                sender.pubkey(),
+                metadata.pubkey(),
+                mint,
+                recipient,
+                partner,
+                &params,
+            ),
+        ),
+        (
+            payer.pubkey(),
+            streamflow_sdk::instructions::create_unchecked_with_payer(
+                payer.pubkey(),
+                sender.pubkey(),
+                metadata.pubkey(),
+                mint,
+                recipient,
+                partner,
+                &params,
+            ),
+        ),
+    ];
+    for (tx, (funder, create)) in sent.iter().zip(expected) {
+        assert!(tx.is_signed());
+        assert_eq!(tx.message.account_keys[0], funder);
+        let allocate = create_account(
+            &funder,
+            &metadata.pubkey(),
+            Rent::default().minimum_balance(METADATA_LEN),
+            METADATA_LEN as u64,
+            &streamflow_sdk::id(),
+        );
+        let expected = Transaction::new_with_payer(&[allocate, create], Some(&funder));
+        assert_eq!(tx.message.instructions, expected.message.instructions);
+        assert_eq!(tx.message.account_keys, expected.message.account_keys);
+    }
+}
+
+#[test]
+fn creates_with_payer_v2() {
+    let client = StreamflowClient::new(MockRpc::default());
+    let (params, mint, recipient) = create_params();
+    let (payer, sender) = (Keypair::new(), Keypair::new());
+
+    let (metadata, signature) = block_on(client.create_unchecked_with_payer_v2(
+        &payer,
+        &sender,
+        mint,
+        recipient,
+        Pubkey::new_unique(),
+        &params,
+    ))
+    .unwrap();
+    // the PDA is derived from the payer
+    let (expected, _) =
+        derive_metadata(&mint, &payer.pubkey(), params.nonce, &streamflow_sdk::id());
+    assert_eq!(metadata, expected);
+
+    let sent = client.rpc().sent.lock().unwrap();
+    assert_eq!(sent[0].signatures[0], signature);
+    assert_eq!(sent[0].signatures.len(), 2);
+    assert!(sent[0].message.account_keys.contains(&metadata));
+}
+
+#[test]
+fn simulates_compute_units() {
+    let client = StreamflowClient::new(MockRpc::default());
+    let (payer, metadata) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let instructions = [
+        streamflow_sdk::instructions::pause(payer, metadata),
+        streamflow_sdk::instructions::unpause(payer, metadata),
+    ];
+    let units = block_on(client.simulate_compute_units(&payer, &instructions)).unwrap();
+    assert_eq!(units, 2 * UNITS_PER_INSTRUCTION);
+
+    // simulated transactions are neither signed nor given a blockhash
+    let simulated = client.rpc().sent.lock().unwrap().pop().unwrap();
+    assert_eq!(simulated.message.account_keys[0], payer);
+    assert!(simulated
+        .signatures
+        .iter()
+        .all(|s| *s == Signature::default()));
+    assert_eq!(simulated.message.recent_blockhash, Hash::default());
+}
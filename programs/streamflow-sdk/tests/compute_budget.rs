@@ -0,0 +1,147 @@
+#[path = "../../streamflow-sdk-core/tests/common/mod.rs"]
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use streamflow_sdk::compute_budget::{
+    budgeted_compute_units, set_compute_unit_limit, set_compute_unit_price, with_compute_budget,
+    ComputeUnits, MAX_COMPUTE_UNITS,
+};
+use streamflow_sdk::instructions;
+use streamflow_sdk::state::Cluster;
+
+const UNITS: ComputeUnits = ComputeUnits {
+    create: 1,
+    create_unchecked: 2,
+    withdraw: 3,
+    cancel: 4,
+    topup: 5,
+    pause: 6,
+    unpause: 7,
+    update: 8,
+    transfer_recipient: 9,
+    transfer_sender: 10,
+};
+
+fn withdraw() -> Instruction {
+    let contract = common::fixture();
+    instructions::withdraw(contract.recipient, Pubkey::new_unique(), &contract, 1_000)
+}
+
+#[test]
+fn budgets_streamflow_instructions() {
+    let contract = common::fixture();
+    let (sender, metadata) = (contract.sender, Pubkey::new_unique());
+    let create = instructions::create_v2(
+        sender,
+        contract.mint,
+        contract.recipient,
+        contract.partner,
+        &contract.ix,
+    );
+    let unchecked = instructions::create_unchecked_with_payer_v2(
+        Pubkey::new_unique(),
+        sender,
+        contract.mint,
+        contract.recipient,
+        contract.partner,
+        &contract.ix,
+    );
+    for (ix, units) in [
+        (create, 1),
+        (unchecked, 2),
+        (withdraw(), 3),
+        (instructions::cancel(sender, metadata, &contract), 4),
+        (instructions::topup(sender, metadata, &contract, 1), 5),
+        (instructions::pause(sender, metadata), 6),
+        (instructions::unpause(sender, metadata), 7),
+    ] {
+        assert_eq!(budgeted_compute_units(&ix, &UNITS), Some(units));
+    }
+    assert_eq!(
+        budgeted_compute_units(&set_compute_unit_price(1), &UNITS),
+        None
+    );
+}
+
+#[test]
+fn budgets_both_deployments() {
+    for cluster in [Cluster::Mainnet, Cluster::Devnet] {
+        let mut ix = withdraw();
+        ix.program_id = cluster.program_id();
+        assert_eq!(budgeted_compute_units(&ix, &UNITS), Some(3));
+    }
+    let mut ix = withdraw();
+    ix.program_id = Pubkey::new_unique();
+    assert_eq!(budgeted_compute_units(&ix, &UNITS), None);
+}
+
+#[test]
+fn prepends_compute_unit_limit() {
+    let instructions = [set_compute_unit_price(10), withdraw(), withdraw()];
+    assert_eq!(
+        with_compute_budget(&instructions, &UNITS, 100),
+        [
+            set_compute_unit_limit(106),
+            instructions[0].clone(),
+            instructions[1].clone(),
+            instructions[2].clone()
+        ]
+    );
+
+    let units = ComputeUnits {
+        withdraw: MAX_COMPUTE_UNITS,
+        ..UNITS
+    };
+    assert_eq!(
+        with_compute_budget(&instructions, &units, 0)[0],
+        set_compute_unit_limit(MAX_COMPUTE_UNITS)
+    );
+}
+
+#[test]
+fn replaces_compute_unit_limit() {
+    let instructions = [
+        set_compute_unit_price(10),
+        set_compute_unit_limit(1),
+        withdraw(),
+        set_compute_unit_limit(2),
+    ];
+    assert_eq!(
+        with_compute_budget(&instructions, &UNITS, 0),
+        [
+            instructions[0].clone(),
+            set_compute_unit_limit(3),
+            instructions[2].clone()
+        ]
+    );
+}
+
+#[test]
+fn keeps_larger_compute_unit_limit() {
+    let instructions = [set_compute_unit_limit(500), withdraw()];
+    assert_eq!(with_compute_budget(&instructions, &UNITS, 0), instructions);
+}
+
+#[test]
+fn default_budgets_every_method() {
+    let units = ComputeUnits::default();
+    for budget in [
+        units.create,
+        units.create_unchecked,
+        units.withdraw,
+        units.cancel,
+        units.topup,
+        units.pause,
+        units.unpause,
+        units.update,
+        units.transfer_recipient,
+        units.transfer_sender,
+    ] {
+        assert!(budget > 0 && budget < MAX_COMPUTE_UNITS);
+    }
+    assert_eq!(
+        with_compute_budget(&[withdraw()], &units, 0)[0],
+        set_compute_unit_limit(units.withdraw)
+    );
+}
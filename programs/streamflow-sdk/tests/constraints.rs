@@ -0,0 +1,429 @@
+#[path = "../../streamflow-sdk-core/tests/common/mod.rs"]
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use streamflow_sdk::accounts;
+use streamflow_sdk::constraints::{
+    is_fee_oracle, is_streamflow_treasury, is_withdrawor, load_contract, AccountError, ValidateKeys,
+};
+use streamflow_sdk::decode::{decode_instruction, StreamflowInstruction};
+use streamflow_sdk::instructions;
+use streamflow_sdk::state::{
+    find_escrow_account, Contract, FEE_ORACLE, FEE_ORACLE_ADDRESS, STREAMFLOW_TREASURY,
+    STRM_TREASURY, WITHDRAWOR, WITHDRAWOR_ADDRESS,
+};
+
+/// Metadata key and its Stream, with the escrow derived from the key
+fn stream() -> (Pubkey, Contract) {
+    let metadata = Pubkey::new_unique();
+    let mut contract = common::fixture();
+    contract.magic = 1;
+    contract.old_metadata = Pubkey::default();
+    contract.escrow_tokens = find_escrow_account(metadata.as_ref(), &streamflow_sdk::id()).0;
+    contract.streamflow_treasury = STREAMFLOW_TREASURY;
+    contract.ix.automatic_withdrawal = false;
+    contract.ix.cancelable_by_sender = true;
+    contract.ix.cancelable_by_recipient = false;
+    (metadata, contract)
+}
+
+fn decode(ix: Instruction) -> StreamflowInstruction {
+    let keys: Vec<_> = ix.accounts.iter().map(|a| a.pubkey).collect();
+    decode_instruction(&ix.data, &keys).unwrap()
+}
+
+/// Change of an account set and the error it causes
+type Mutation<T> = (fn(&mut T), AccountError);
+
+/// Check that built accounts are valid and every mutation is rejected with its error
+fn assert_rejects<T: ValidateKeys>(
+    accounts: impl Fn() -> T,
+    contract: &Contract,
+    mutations: &[Mutation<T>],
+) {
+    assert_eq!(accounts().validate_keys(contract), Ok(()));
+    for (mutate, error) in mutations {
+        let mut accounts = accounts();
+        mutate(&mut accounts);
+        assert_eq!(accounts.validate_keys(contract), Err(*error));
+    }
+}
+
+#[test]
+fn protocol_constants() {
+    assert_eq!(STREAMFLOW_TREASURY, STRM_TREASURY.parse().unwrap());
+    assert_eq!(WITHDRAWOR, WITHDRAWOR_ADDRESS.parse().unwrap());
+    assert_eq!(FEE_ORACLE, FEE_ORACLE_ADDRESS.parse().unwrap());
+    assert!(is_streamflow_treasury(&STREAMFLOW_TREASURY));
+    assert!(is_withdrawor(&WITHDRAWOR));
+    assert!(is_fee_oracle(&FEE_ORACLE));
+    assert!(!is_withdrawor(&FEE_ORACLE));
+}
+
+#[test]
+fn validates_withdraw() {
+    let (metadata, contract) = stream();
+    let accounts = || match decode(instructions::withdraw(
+        contract.recipient,
+        metadata,
+        &contract,
+        1,
+    )) {
+        StreamflowInstruction::Withdraw { accounts, .. } => accounts,
+        _ => panic!("not a withdraw"),
+    };
+    let mutations: [Mutation<accounts::Withdraw>; 11] = [
+        (
+            |a| a.authority = Pubkey::new_unique(),
+            AccountError::AuthorityMismatch,
+        ),
+        (
+            |a| a.recipient = Pubkey::new_unique(),
+            AccountError::RecipientMismatch,
+        ),
+        (
+            |a| a.recipient_tokens = Pubkey::new_unique(),
+            AccountError::RecipientTokensMismatch,
+        ),
+        (
+            |a| a.escrow_tokens = Pubkey::new_unique(),
+            AccountError::EscrowMismatch,
+        ),
+        // escrow of the Stream passed with another metadata
+        (
+            |a| a.metadata = Pubkey::new_unique(),
+            AccountError::EscrowMismatch,
+        ),
+        (
+            |a| a.mint = Pubkey::new_unique(),
+            AccountError::MintMismatch,
+        ),
+        (
+            |a| a.streamflow_treasury = Pubkey::new_unique(),
+            AccountError::StreamflowTreasuryMismatch,
+        ),
+        (
+            |a| a.streamflow_treasury_tokens = Pubkey::new_unique(),
+            AccountError::StreamflowTreasuryTokensMismatch,
+        ),
+        (
+            |a| a.partner = Pubkey::new_unique(),
+            AccountError::PartnerMismatch,
+        ),
+        (
+            |a| a.partner_tokens = Pubkey::new_unique(),
+            AccountError::PartnerTokensMismatch,
+        ),
+        (
+            |a| a.token_program = Pubkey::new_unique(),
+            AccountError::InvalidTokenProgram,
+        ),
+    ];
+    assert_rejects(accounts, &contract, &mutations);
+
+    // anyone can withdraw with automatic withdrawals
+    let mut automatic = contract.clone();
+    automatic.ix.automatic_withdrawal = true;
+    let mut accounts = accounts();
+    accounts.authority = Pubkey::new_unique();
+    assert_eq!(accounts.validate_keys(&automatic), Ok(()));
+}
+
+#[test]
+fn validates_cancel() {
+    let (metadata, contract) = stream();
+    let accounts = || match decode(instructions::cancel(contract.sender, metadata, &contract)) {
+        StreamflowInstruction::Cancel { accounts } => accounts,
+        _ => panic!("not a cancel"),
+    };
+    let mutations: [Mutation<accounts::Cancel>; 8] = [
+        (
+            |a| a.authority = Pubkey::new_unique(),
+            AccountError::AuthorityMismatch,
+        ),
+        (
+            |a| a.sender = Pubkey::new_unique(),
+            AccountError::SenderMismatch,
+        ),
+        (
+            |a| a.sender_tokens = Pubkey::new_unique(),
+            AccountError::SenderTokensMismatch,
+        ),
+        (
+            |a| a.recipient = Pubkey::new_unique(),
+            AccountError::RecipientMismatch,
+        ),
+        (
+            |a| a.recipient_tokens = Pubkey::new_unique(),
+            AccountError::RecipientTokensMismatch,
+        ),
+        (
+            |a| a.metadata = Pubkey::new_unique(),
+            AccountError::EscrowMismatch,
+        ),
+        (
+            |a| a.mint = Pubkey::new_unique(),
+            AccountError::MintMismatch,
+        ),
+        (
+            |a| a.token_program = Pubkey::new_unique(),
+            AccountError::InvalidTokenProgram,
+        ),
+    ];
+    assert_rejects(accounts, &contract, &mutations);
+
+    // the recipient can't cancel unless the Stream allows it
+    let mut accounts = accounts();
+    accounts.authority = contract.recipient;
+    assert_eq!(
+        accounts.validate_keys(&contract),
+        Err(AccountError::AuthorityMismatch)
+    );
+    let mut cancelable = contract.clone();
+    cancelable.ix.cancelable_by_recipient = true;
+    assert_eq!(accounts.validate_keys(&cancelable), Ok(()));
+    cancelable.ix.cancelable_by_sender = false;
+    accounts.authority = contract.sender;
+    assert_eq!(
+        accounts.validate_keys(&cancelable),
+        Err(AccountError::AuthorityMismatch)
+    );
+}
+
+#[test]
+fn validates_topup() {
+    let (metadata, contract) = stream();
+    let accounts = || match decode(instructions::topup(contract.sender, metadata, &contract, 1)) {
+        StreamflowInstruction::Topup { accounts, .. } => accounts,
+        _ => panic!("not a topup"),
+    };
+    let mutations: [Mutation<accounts::Topup>; 5] = [
+        (
+            |a| a.sender = Pubkey::new_unique(),
+            AccountError::SenderMismatch,
+        ),
+        (
+            |a| a.escrow_tokens = Pubkey::new_unique(),
+            AccountError::EscrowMismatch,
+        ),
+        (
+            |a| a.withdrawor = Pubkey::new_unique(),
+            AccountError::WithdraworMismatch,
+        ),
+        (
+            |a| a.streamflow_treasury = Pubkey::new_unique(),
+            AccountError::StreamflowTreasuryMismatch,
+        ),
+        (
+            |a| a.system_program = Pubkey::new_unique(),
+            AccountError::InvalidSystemProgram,
+        ),
+    ];
+    assert_rejects(accounts, &contract, &mutations);
+
+    // owner and mint of the funding account aren't known from keys
+    let mut topup = accounts();
+    topup.sender_tokens = Pubkey::new_unique();
+    assert_eq!(topup.validate_keys(&contract), Ok(()));
+}
+
+#[test]
+fn derives_escrow_from_old_metadata() {
+    let (metadata, mut contract) = stream();
+    let old_metadata = Pubkey::new_unique();
+    contract.old_metadata = old_metadata;
+    let ix = instructions::withdraw(contract.recipient, metadata, &contract, 1);
+    let StreamflowInstruction::Withdraw { accounts, .. } = decode(ix) else {
+        panic!("not a withdraw");
+    };
+    assert_eq!(
+        accounts.validate_keys(&contract),
+        Err(AccountError::EscrowMismatch)
+    );
+    contract.escrow_tokens = find_escrow_account(old_metadata.as_ref(), &streamflow_sdk::id()).0;
+    let ix = instructions::withdraw(contract.recipient, metadata, &contract, 1);
+    let StreamflowInstruction::Withdraw { accounts, .. } = decode(ix) else {
+        panic!("not a withdraw");
+    };
+    assert_eq!(accounts.validate_keys(&contract), Ok(()));
+}
+
+#[test]
+fn loads_contract() {
+    let (key, contract) = stream();
+    let data = contract.try_to_vec().unwrap();
+    let (mut lamports, mut buffer) = (0, data.clone());
+    let owner = streamflow_sdk::id();
+    let metadata = AccountInfo::new(
+        &key,
+        false,
+        false,
+        &mut lamports,
+        &mut buffer,
+        &owner,
+        false,
+        0,
+    );
+    assert_eq!(
+        load_contract(&metadata).unwrap().try_to_vec().unwrap(),
+        data
+    );
+
+    let other = Pubkey::new_unique();
+    let (mut lamports, mut buffer) = (0, data.clone());
+    let metadata = AccountInfo::new(
+        &key,
+        false,
+        false,
+        &mut lamports,
+        &mut buffer,
+        &other,
+        false,
+        0,
+    );
+    assert_eq!(
+        load_contract(&metadata).unwrap_err(),
+        AccountError::InvalidMetadataOwner
+    );
+
+    // allocated but not written
+    for mut buffer in [vec![0; data.len()], data[..100].to_vec()] {
+        let mut lamports = 0;
+        let metadata = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut buffer,
+            &owner,
+            false,
+            0,
+        );
+        assert_eq!(
+            load_contract(&metadata).unwrap_err(),
+            AccountError::InvalidMetadata
+        );
+    }
+}
+
+/// Account with leaked storage, so CPI account sets can hold it
+#[cfg(feature = "cpi")]
+fn account_info(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> AccountInfo<'static> {
+    AccountInfo::new(
+        Box::leak(Box::new(key)),
+        false,
+        false,
+        Box::leak(Box::new(0)),
+        data.leak(),
+        Box::leak(Box::new(owner)),
+        false,
+        0,
+    )
+}
+
+#[cfg(feature = "cpi")]
+#[test]
+fn validates_cpi_accounts() {
+    use streamflow_sdk::constraints::ValidateAccounts;
+
+    let (key, contract) = stream();
+    let cpi_accounts = |metadata: AccountInfo<'static>| {
+        let info = |key| account_info(key, Pubkey::default(), vec![]);
+        streamflow_sdk::cpi::accounts::Withdraw {
+            authority: info(contract.recipient),
+            recipient: info(contract.recipient),
+            recipient_tokens: info(contract.recipient_tokens),
+            metadata,
+            escrow_tokens: info(contract.escrow_tokens),
+            streamflow_treasury: info(contract.streamflow_treasury),
+            streamflow_treasury_tokens: info(contract.streamflow_treasury_tokens),
+            partner: info(contract.partner),
+            partner_tokens: info(contract.partner_tokens),
+            mint: info(contract.mint),
+            token_program: info(anchor_spl::token::ID),
+        }
+    };
+    let data = contract.try_to_vec().unwrap();
+    let metadata = account_info(key, streamflow_sdk::id(), data.clone());
+    let loaded = cpi_accounts(metadata).validate().unwrap();
+    assert_eq!(loaded.try_to_vec().unwrap(), data);
+
+    let metadata = account_info(key, Pubkey::new_unique(), data.clone());
+    assert_eq!(
+        cpi_accounts(metadata).validate().unwrap_err(),
+        AccountError::InvalidMetadataOwner
+    );
+    // the escrow is derived from the passed metadata
+    let metadata = account_info(Pubkey::new_unique(), streamflow_sdk::id(), data);
+    assert_eq!(
+        cpi_accounts(metadata).validate().unwrap_err(),
+        AccountError::EscrowMismatch
+    );
+}
+
+/// Initialized SPL token account
+#[cfg(feature = "cpi")]
+fn token_account(owner: Pubkey, mint: Pubkey) -> Vec<u8> {
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token::spl_token::state::{Account, AccountState};
+
+    let mut data = vec![0; Account::LEN];
+    Account {
+        mint,
+        owner,
+        state: AccountState::Initialized,
+        ..Account::default()
+    }
+    .pack_into_slice(&mut data);
+    data
+}
+
+#[cfg(feature = "cpi")]
+#[test]
+fn validates_topup_sender_tokens() {
+    use streamflow_sdk::constraints::{is_token_account, ValidateAccounts};
+
+    let (key, contract) = stream();
+    let cpi_accounts = |sender_tokens: AccountInfo<'static>| {
+        let info = |key| account_info(key, Pubkey::default(), vec![]);
+        streamflow_sdk::cpi::accounts::Topup {
+            sender: info(contract.sender),
+            sender_tokens,
+            metadata: account_info(key, streamflow_sdk::id(), contract.try_to_vec().unwrap()),
+            escrow_tokens: info(contract.escrow_tokens),
+            streamflow_treasury: info(contract.streamflow_treasury),
+            streamflow_treasury_tokens: info(contract.streamflow_treasury_tokens),
+            withdrawor: info(WITHDRAWOR),
+            partner: info(contract.partner),
+            partner_tokens: info(contract.partner_tokens),
+            mint: info(contract.mint),
+            token_program: info(anchor_spl::token::ID),
+            system_program: info(anchor_lang::system_program::ID),
+        }
+    };
+    let tokens = |owner, mint, program| {
+        account_info(Pubkey::new_unique(), program, token_account(owner, mint))
+    };
+
+    // not the associated token account of the sender
+    let sender_tokens = tokens(contract.sender, contract.mint, anchor_spl::token::ID);
+    assert!(is_token_account(
+        &sender_tokens,
+        &contract.sender,
+        &contract.mint
+    ));
+    assert!(cpi_accounts(sender_tokens).validate().is_ok());
+
+    for sender_tokens in [
+        tokens(Pubkey::new_unique(), contract.mint, anchor_spl::token::ID),
+        tokens(contract.sender, Pubkey::new_unique(), anchor_spl::token::ID),
+        tokens(contract.sender, contract.mint, Pubkey::new_unique()),
+        account_info(Pubkey::new_unique(), anchor_spl::token::ID, vec![]),
+    ] {
+        assert_eq!(
+            cpi_accounts(sender_tokens).validate().unwrap_err(),
+            AccountError::SenderTokensMismatch
+        );
+    }
+}
@@ -0,0 +1,184 @@
+#[path = "../../streamflow-sdk-core/tests/common/mod.rs"]
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::InstructionData;
+use streamflow_sdk::decode::{decode_instruction, DecodeError, StreamflowInstruction};
+use streamflow_sdk::instructions;
+use streamflow_sdk::update::{update_instruction, UpdateArgs};
+
+fn parts(
+    args: &impl InstructionData,
+    accounts: &impl ToAccountMetas,
+) -> (Vec<u8>, Vec<AccountMeta>) {
+    (args.data(), accounts.to_account_metas(None))
+}
+
+/// Data and accounts of a decoded instruction, encoded again
+fn encode(ix: &StreamflowInstruction) -> (Vec<u8>, Vec<AccountMeta>) {
+    use streamflow_sdk::instruction as args;
+    match ix {
+        StreamflowInstruction::Create { args, accounts } => parts(args, accounts),
+        StreamflowInstruction::CreateV2 { args, accounts } => parts(args, accounts),
+        StreamflowInstruction::CreateUnchecked { args, accounts } => parts(args, accounts),
+        StreamflowInstruction::CreateUncheckedV2 { args, accounts } => parts(args, accounts),
+        StreamflowInstruction::CreateUncheckedWithPayer { args, accounts } => parts(args, accounts),
+        StreamflowInstruction::CreateUncheckedWithPayerV2 { args, accounts } => {
+            parts(args, accounts)
+        }
+        StreamflowInstruction::Update { args, accounts } => parts(args, accounts),
+        StreamflowInstruction::Withdraw { args, accounts } => parts(args, accounts),
+        StreamflowInstruction::Cancel { accounts } => parts(&args::Cancel {}, accounts),
+        StreamflowInstruction::Pause { accounts } => parts(&args::Pause {}, accounts),
+        StreamflowInstruction::Unpause { accounts } => parts(&args::Unpause {}, accounts),
+        StreamflowInstruction::TransferRecipient { accounts } => {
+            parts(&args::TransferRecipient {}, accounts)
+        }
+        StreamflowInstruction::TransferSender { accounts } => {
+            parts(&args::TransferSender {}, accounts)
+        }
+        StreamflowInstruction::Topup { args, accounts } => parts(args, accounts),
+    }
+}
+
+/// Instruction of every method of the program with its name
+fn all_instructions() -> Vec<(&'static str, Instruction)> {
+    let contract = common::fixture();
+    let params = &contract.ix;
+    let (sender, recipient, partner, mint) = (
+        contract.sender,
+        contract.recipient,
+        contract.partner,
+        contract.mint,
+    );
+    let (payer, metadata) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let update = UpdateArgs::new().set_rate(42).disable_sender_transfer();
+    vec![
+        (
+            "create",
+            instructions::create(sender, metadata, mint, recipient, partner, params),
+        ),
+        (
+            "create_v2",
+            instructions::create_v2(sender, mint, recipient, partner, params),
+        ),
+        (
+            "create_unchecked",
+            instructions::create_unchecked(sender, metadata, mint, recipient, partner, params),
+        ),
+        (
+            "create_unchecked_v2",
+            instructions::create_unchecked_v2(sender, mint, recipient, partner, params),
+        ),
+        (
+            "create_unchecked_with_payer",
+            instructions::create_unchecked_with_payer(
+                payer, sender, metadata, mint, recipient, partner, params,
+            ),
+        ),
+        (
+            "create_unchecked_with_payer_v2",
+            instructions::create_unchecked_with_payer_v2(
+                payer, sender, mint, recipient, partner, params,
+            ),
+        ),
+        (
+            "update",
+            update_instruction(sender, metadata, update).unwrap(),
+        ),
+        (
+            "withdraw",
+            instructions::withdraw(recipient, metadata, &contract, 1_000),
+        ),
+        ("cancel", instructions::cancel(sender, metadata, &contract)),
+        ("pause", instructions::pause(sender, metadata)),
+        ("unpause", instructions::unpause(sender, metadata)),
+        (
+            "transfer_recipient",
+            instructions::transfer_recipient(recipient, metadata, mint, Pubkey::new_unique()),
+        ),
+        (
+            "transfer_sender",
+            instructions::transfer_sender(sender, Pubkey::new_unique(), metadata, mint),
+        ),
+        (
+            "topup",
+            instructions::topup(sender, metadata, &contract, 1_000),
+        ),
+    ]
+}
+
+fn keys(ix: &Instruction) -> Vec<Pubkey> {
+    ix.accounts.iter().map(|a| a.pubkey).collect()
+}
+
+#[test]
+fn round_trips_every_instruction() {
+    for (name, ix) in all_instructions() {
+        let decoded = decode_instruction(&ix.data, &keys(&ix)).unwrap();
+        assert_eq!(decoded.name(), name);
+        // named accounts are in instruction order with the same signer and writable flags
+        assert_eq!(
+            encode(&decoded),
+            (ix.data.clone(), ix.accounts.clone()),
+            "{name}"
+        );
+        assert!(keys(&ix).contains(&decoded.metadata()), "{name}");
+    }
+}
+
+#[test]
+fn names_accounts() {
+    let contract = common::fixture();
+    let metadata = Pubkey::new_unique();
+    let ix = instructions::withdraw(contract.recipient, metadata, &contract, 1_000);
+    let StreamflowInstruction::Withdraw { args, accounts } =
+        decode_instruction(&ix.data, &keys(&ix)).unwrap()
+    else {
+        panic!("not a withdraw");
+    };
+    assert_eq!(args.amount, 1_000);
+    assert_eq!(accounts.authority, contract.recipient);
+    assert_eq!(accounts.recipient_tokens, contract.recipient_tokens);
+    assert_eq!(accounts.metadata, metadata);
+    assert_eq!(accounts.escrow_tokens, contract.escrow_tokens);
+    assert_eq!(accounts.mint, contract.mint);
+
+    let debug = format!("{:?}", decode_instruction(&ix.data, &keys(&ix)).unwrap());
+    assert!(debug.starts_with("Withdraw { args: Withdraw { amount: 1000 }"));
+    assert!(debug.contains(&format!("metadata: {metadata}")));
+}
+
+#[test]
+fn ignores_extra_accounts() {
+    let ix = instructions::pause(Pubkey::new_unique(), Pubkey::new_unique());
+    let mut accounts = keys(&ix);
+    accounts.push(Pubkey::new_unique());
+    let decoded = decode_instruction(&ix.data, &accounts).unwrap();
+    assert_eq!(encode(&decoded), (ix.data, ix.accounts));
+}
+
+#[test]
+fn rejects_invalid_instructions() {
+    let contract = common::fixture();
+    let ix = instructions::topup(Pubkey::new_unique(), Pubkey::new_unique(), &contract, 1);
+    let accounts = keys(&ix);
+
+    assert_eq!(
+        decode_instruction(&ix.data[..7], &accounts).unwrap_err(),
+        DecodeError::UnknownInstruction
+    );
+    assert_eq!(
+        decode_instruction(&[0; 16], &accounts).unwrap_err(),
+        DecodeError::UnknownInstruction
+    );
+    assert_eq!(
+        decode_instruction(&ix.data[..12], &accounts).unwrap_err(),
+        DecodeError::InvalidData
+    );
+    assert_eq!(
+        decode_instruction(&ix.data, &accounts[..accounts.len() - 1]).unwrap_err(),
+        DecodeError::NotEnoughAccounts
+    );
+}
@@ -0,0 +1,247 @@
+#[path = "../../streamflow-sdk-core/tests/common/mod.rs"]
+mod common;
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::*;
+use common::{METADATA_V1, METADATA_V2, METADATA_V3};
+use streamflow_sdk::state::{Contract, METADATA_LEN, STRM_TREASURY};
+use streamflow_sdk::versioned::VersionedContract;
+
+/// Number of bytes `Contract` occupies in the account, the rest is zero padding
+const CONTRACT_LEN: usize = 779;
+/// `magic` of the synthetic fixtures, not the value of Streams of the deployed program
+const SYNTHETIC_MAGIC: u64 = 0x4d52_5453_5f4d_5254;
+
+fn key(byte: u8) -> Pubkey {
+    Pubkey::new_from_array([byte; 32])
+}
+
+fn name(name: &str) -> [u8; 64] {
+    let mut bytes = [0; 64];
+    bytes[..name.len()].copy_from_slice(name.as_bytes());
+    bytes
+}
+
+/// Stream created before pauses and rate updates were introduced
+fn expected_v1() -> Contract {
+    let treasury = Pubkey::from_str(STRM_TREASURY).unwrap();
+    let mut contract = common::contract(
+        1_700_000_000,
+        1_000_000_000,
+        86_400,
+        2_739_726,
+        1_702_592_000,
+        100_000_000,
+    );
+    contract.magic = SYNTHETIC_MAGIC;
+    contract.version = 1;
+    contract.amount_withdrawn = 250_000_000;
+    contract.end_time = 1_731_536_000;
+    contract.last_withdrawn_at = 1_710_000_000;
+    contract.sender = key(1);
+    contract.sender_tokens = key(2);
+    contract.recipient = key(3);
+    contract.recipient_tokens = key(4);
+    contract.mint = key(5);
+    contract.escrow_tokens = key(6);
+    contract.streamflow_treasury = treasury;
+    contract.streamflow_treasury_tokens = key(7);
+    contract.streamflow_fee_total = 2_500_000;
+    contract.streamflow_fee_withdrawn = 625_000;
+    contract.streamflow_fee_percent = 0.25;
+    contract.partner = treasury;
+    contract.partner_tokens = key(7);
+    contract.ix.can_topup = true;
+    contract.ix.stream_name = name("Team vesting Q1");
+    contract.ix.pausable = false;
+    contract.ix.can_update_rate = false;
+    contract
+}
+
+/// Stream that can be paused and updated, with automatic withdrawals
+fn expected_v2() -> Contract {
+    let mut contract = expected_v1();
+    contract.version = 2;
+    contract.ix.automatic_withdrawal = true;
+    contract.ix.withdraw_frequency = 3_600;
+    contract.ix.pausable = true;
+    contract.ix.can_update_rate = true;
+    contract.pause_cumulative = 172_800;
+    contract.last_rate_change_time = 1_705_000_000;
+    contract.funds_unlocked_at_last_rate_change = 150_000_000;
+    contract
+}
+
+/// Stream created with a v2 instruction, metadata is a PDA
+fn expected_v3() -> Contract {
+    let mut contract = expected_v2();
+    contract.version = 3;
+    contract.ix.is_pda = true;
+    contract.ix.nonce = 7;
+    contract.creation_fee = 10_000_000;
+    contract.creation_fee_claimed = true;
+    contract.auto_claim_fee = 2_000_000;
+    contract.payer = key(8);
+    contract.bump = 254;
+    contract
+}
+
+fn u64_at(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn f32_at(data: &[u8], offset: usize) -> f32 {
+    f32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn bool_at(data: &[u8], offset: usize) -> bool {
+    match data[offset] {
+        0 => false,
+        1 => true,
+        b => panic!("invalid bool {b} at offset {offset}"),
+    }
+}
+
+fn key_at(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::try_from(&data[offset..offset + 32]).unwrap()
+}
+
+/// Check every field of `c` at its fixed offset in the raw account data
+fn assert_offsets(data: &[u8], c: &Contract) {
+    assert_eq!(u64_at(data, 0), c.magic);
+    assert_eq!(data[8], c.version);
+    assert_eq!(u64_at(data, 9), c.created_at);
+    assert_eq!(u64_at(data, 17), c.amount_withdrawn);
+    assert_eq!(u64_at(data, 25), c.canceled_at);
+    assert_eq!(u64_at(data, 33), c.end_time);
+    assert_eq!(u64_at(data, 41), c.last_withdrawn_at);
+    assert_eq!(key_at(data, 49), c.sender);
+    assert_eq!(key_at(data, 81), c.sender_tokens);
+    assert_eq!(key_at(data, 113), c.recipient);
+    assert_eq!(key_at(data, 145), c.recipient_tokens);
+    assert_eq!(key_at(data, 177), c.mint);
+    assert_eq!(key_at(data, 209), c.escrow_tokens);
+    assert_eq!(key_at(data, 241), c.streamflow_treasury);
+    assert_eq!(key_at(data, 273), c.streamflow_treasury_tokens);
+    assert_eq!(u64_at(data, 305), c.streamflow_fee_total);
+    assert_eq!(u64_at(data, 313), c.streamflow_fee_withdrawn);
+    assert_eq!(f32_at(data, 321), c.streamflow_fee_percent);
+    assert_eq!(key_at(data, 325), c.partner);
+    assert_eq!(key_at(data, 357), c.partner_tokens);
+    assert_eq!(u64_at(data, 389), c.partner_fee_total);
+    assert_eq!(u64_at(data, 397), c.partner_fee_withdrawn);
+    assert_eq!(f32_at(data, 405), c.partner_fee_percent);
+
+    assert_eq!(u64_at(data, 409), c.ix.start_time);
+    assert_eq!(u64_at(data, 417), c.ix.net_amount_deposited);
+    assert_eq!(u64_at(data, 425), c.ix.period);
+    assert_eq!(u64_at(data, 433), c.ix.amount_per_period);
+    assert_eq!(u64_at(data, 441), c.ix.cliff);
+    assert_eq!(u64_at(data, 449), c.ix.cliff_amount);
+    assert_eq!(bool_at(data, 457), c.ix.cancelable_by_sender);
+    assert_eq!(bool_at(data, 458), c.ix.cancelable_by_recipient);
+    assert_eq!(bool_at(data, 459), c.ix.automatic_withdrawal);
+    assert_eq!(bool_at(data, 460), c.ix.transferable_by_sender);
+    assert_eq!(bool_at(data, 461), c.ix.transferable_by_recipient);
+    assert_eq!(bool_at(data, 462), c.ix.can_topup);
+    assert_eq!(data[463..527], c.ix.stream_name);
+    assert_eq!(u64_at(data, 527), c.ix.withdraw_frequency);
+    assert_eq!(u32_at(data, 535), c.ix.ghost);
+    assert_eq!(bool_at(data, 539), c.ix.pausable);
+    assert_eq!(bool_at(data, 540), c.ix.can_update_rate);
+    assert_eq!(u32_at(data, 541), c.ix.ghost2);
+    assert_eq!(bool_at(data, 545), c.ix.is_pda);
+    assert_eq!(u32_at(data, 546), c.ix.nonce);
+    assert_eq!(data[550..671], c.ix_padding);
+
+    assert_eq!(bool_at(data, 671), c.closed);
+    assert_eq!(u64_at(data, 672), c.current_pause_start);
+    assert_eq!(u64_at(data, 680), c.pause_cumulative);
+    assert_eq!(u64_at(data, 688), c.last_rate_change_time);
+    assert_eq!(u64_at(data, 696), c.funds_unlocked_at_last_rate_change);
+    assert_eq!(u32_at(data, 704), c.creation_fee);
+    assert_eq!(bool_at(data, 708), c.creation_fee_claimed);
+    assert_eq!(u32_at(data, 709), c.auto_claim_fee);
+    assert_eq!(bool_at(data, 713), c.auto_claim_fee_claimed);
+    assert_eq!(key_at(data, 714), c.old_metadata);
+    assert_eq!(key_at(data, 746), c.payer);
+    assert_eq!(data[778], c.bump);
+    assert!(data[CONTRACT_LEN..].iter().all(|b| *b == 0));
+}
+
+/// Check the size of the account and that it re-serializes byte-identically
+fn assert_round_trip(data: &[u8]) -> Contract {
+    assert_eq!(data.len(), METADATA_LEN);
+    let contract = Contract::deserialize(&mut &data[..]).unwrap();
+    assert_offsets(data, &contract);
+
+    let mut serialized = contract.try_to_vec().unwrap();
+    assert_eq!(serialized.len(), CONTRACT_LEN);
+    serialized.resize(METADATA_LEN, 0);
+    assert_eq!(serialized, data);
+    contract
+}
+
+fn assert_golden(data: &[u8], expected: Contract) {
+    assert_offsets(data, &expected);
+    let contract = assert_round_trip(data);
+    assert_eq!(
+        contract.try_to_vec().unwrap(),
+        expected.try_to_vec().unwrap()
+    );
+    assert_eq!(format!("{contract:?}"), format!("{expected:?}"));
+}
+
+#[test]
+fn contract_fits_metadata_account() {
+    let size = expected_v3().try_to_vec().unwrap().len();
+    assert_eq!(size, CONTRACT_LEN);
+    assert!(size <= METADATA_LEN);
+}
+
+#[test]
+fn golden_metadata_v1() {
+    assert_golden(METADATA_V1, expected_v1());
+}
+
+#[test]
+fn golden_metadata_v2() {
+    assert_golden(METADATA_V2, expected_v2());
+}
+
+#[test]
+fn golden_metadata_v3() {
+    assert_golden(METADATA_V3, expected_v3());
+}
+
+#[test]
+fn versioned_decoding() {
+    // older Streams have zero padding in place of the PDA fields
+    for data in [METADATA_V1, METADATA_V2] {
+        let stream = VersionedContract::decode(data).unwrap();
+        assert_eq!(stream.nonce(), None);
+        assert_eq!(stream.bump(), None);
+        assert_eq!(stream.payer(), None);
+        assert_eq!(stream.old_metadata(), None);
+    }
+
+    let v3 = VersionedContract::decode(METADATA_V3).unwrap();
+    assert_eq!(v3.nonce(), Some(7));
+    assert_eq!(v3.bump(), Some(254));
+    assert_eq!(v3.payer(), Some(key(8)));
+    assert_eq!(v3.old_metadata(), None);
+
+    // accessors don't depend on the version the Stream reports
+    let mut contract = v3.contract;
+    contract.version = 1;
+    contract.old_metadata = key(9);
+    let stream = VersionedContract::from(contract);
+    assert_eq!(stream.nonce(), Some(7));
+    assert_eq!(stream.payer(), Some(key(8)));
+    assert_eq!(stream.old_metadata(), Some(key(9)));
+}
@@ -0,0 +1,136 @@
+#[path = "../../streamflow-sdk-core/tests/common/mod.rs"]
+mod common;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use solana_hash::Hash;
+use solana_message::{AddressLookupTableAccount, Message, VersionedMessage};
+use solana_transaction::versioned::VersionedTransaction;
+use streamflow_sdk::compute_budget::set_compute_unit_price;
+use streamflow_sdk::instructions;
+use streamflow_sdk::lookup_table::{
+    cluster_addresses, compile_v0_message, lookup_table_account, lookup_table_addresses,
+    versioned_transaction_size, ProtocolAccounts,
+};
+use streamflow_sdk::state::{Cluster, STREAMFLOW_DEVNET_PROGRAM_ID, STREAMFLOW_PROGRAM_ID};
+
+fn serialized_size(message: VersionedMessage) -> usize {
+    let signatures = message.header().num_required_signatures as usize;
+    let tx = VersionedTransaction {
+        signatures: vec![Default::default(); signatures],
+        message,
+    };
+    bincode::serialize(&tx).unwrap().len()
+}
+
+/// Creates of two Streams with a separate payer, so the transaction has two signers
+fn creates(payer: Pubkey) -> Vec<Instruction> {
+    let contract = common::fixture();
+    (0..2)
+        .map(|_| {
+            instructions::create_unchecked_with_payer(
+                payer,
+                contract.sender,
+                Pubkey::new_unique(),
+                contract.mint,
+                Pubkey::new_unique(),
+                contract.partner,
+                &contract.ix,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn transaction_size_matches_serialization() {
+    let payer = Pubkey::new_unique();
+    let mut instructions = creates(payer);
+    instructions.push(set_compute_unit_price(1));
+    let mint = common::fixture().mint;
+    let table = lookup_table_account(Pubkey::new_unique(), &ProtocolAccounts::mainnet(), &[mint]);
+
+    for tables in [&[][..], &[table][..]] {
+        let message = compile_v0_message(&payer, &instructions, tables, Hash::default()).unwrap();
+        assert_eq!(
+            versioned_transaction_size(&message),
+            serialized_size(message)
+        );
+    }
+    let legacy = VersionedMessage::Legacy(Message::new(&instructions, Some(&payer)));
+    assert_eq!(versioned_transaction_size(&legacy), serialized_size(legacy));
+}
+
+#[test]
+fn lookup_table_shrinks_transaction() {
+    let payer = Pubkey::new_unique();
+    let instructions = creates(payer);
+    let mint = common::fixture().mint;
+    let table = lookup_table_account(Pubkey::new_unique(), &ProtocolAccounts::mainnet(), &[mint]);
+
+    let size = |tables: &[AddressLookupTableAccount]| {
+        let message = compile_v0_message(&payer, &instructions, tables, Hash::default()).unwrap();
+        versioned_transaction_size(&message)
+    };
+    assert!(size(&[table]) < size(&[]));
+}
+
+#[test]
+fn selects_accounts_by_cluster() {
+    let (mainnet, devnet) = (ProtocolAccounts::mainnet(), ProtocolAccounts::devnet());
+    assert_eq!(ProtocolAccounts::new(Cluster::Mainnet), mainnet);
+    assert_eq!(ProtocolAccounts::new(Cluster::Devnet), devnet);
+    assert_eq!(mainnet.program_id, STREAMFLOW_PROGRAM_ID.parse().unwrap());
+    assert_eq!(
+        devnet.program_id,
+        STREAMFLOW_DEVNET_PROGRAM_ID.parse().unwrap()
+    );
+    assert_eq!(
+        ProtocolAccounts {
+            program_id: mainnet.program_id,
+            ..devnet
+        },
+        mainnet
+    );
+    assert_eq!(
+        ProtocolAccounts::new(if cfg!(feature = "devnet") {
+            Cluster::Devnet
+        } else {
+            Cluster::Mainnet
+        })
+        .program_id,
+        streamflow_sdk::id()
+    );
+}
+
+#[test]
+fn uses_given_protocol_accounts() {
+    let protocol = ProtocolAccounts {
+        program_id: Pubkey::new_unique(),
+        treasury: Pubkey::new_unique(),
+        withdrawor: Pubkey::new_unique(),
+        fee_oracle: Pubkey::new_unique(),
+    };
+    let addresses = cluster_addresses(&protocol);
+    for address in [
+        protocol.program_id,
+        protocol.treasury,
+        protocol.withdrawor,
+        protocol.fee_oracle,
+    ] {
+        assert!(addresses.contains(&address));
+    }
+    let mainnet = ProtocolAccounts::mainnet();
+    assert!(!addresses.contains(&mainnet.treasury));
+
+    // mints are added once, after the accounts shared by every Stream
+    let mint = Pubkey::new_unique();
+    let table = lookup_table_addresses(&protocol, &[mint, mint]);
+    assert_eq!(table[..addresses.len()], addresses[..]);
+    assert_eq!(
+        table[addresses.len()..],
+        [
+            mint,
+            anchor_spl::associated_token::get_associated_token_address(&protocol.treasury, &mint)
+        ]
+    );
+}
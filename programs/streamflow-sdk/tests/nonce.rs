@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+use streamflow_sdk::nonce::{
+    allocate_external_id_nonce, external_id_nonce, find_free_nonce, next_unused_nonce,
+    AllocatedNonce, NonceError,
+};
+use streamflow_sdk::state::derive_metadata;
+
+fn keys() -> (Pubkey, Pubkey, Pubkey) {
+    (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        streamflow_sdk::id(),
+    )
+}
+
+#[test]
+fn external_id_nonce_is_stable() {
+    // first 4 bytes of sha256("strm-nonce" || id), big endian
+    assert_eq!(external_id_nonce(b"invoice-42"), 1_137_410_110);
+    assert_eq!(external_id_nonce(b""), 2_354_373_251);
+    assert_ne!(
+        external_id_nonce(b"invoice-42"),
+        external_id_nonce(b"invoice-43")
+    );
+
+    let (mint, payer, pid) = keys();
+    let allocated = allocate_external_id_nonce(&mint, &payer, &pid, b"invoice-42", |_| false);
+    let (metadata, bump) = derive_metadata(&mint, &payer, 1_137_410_110, &pid);
+    assert_eq!(
+        allocated,
+        Ok(AllocatedNonce {
+            nonce: 1_137_410_110,
+            metadata,
+            bump,
+        })
+    );
+}
+
+#[test]
+fn external_id_collision() {
+    // different ids with the same nonce derive the same metadata
+    let nonce = external_id_nonce(b"employee-5282");
+    assert_eq!(nonce, external_id_nonce(b"employee-49268"));
+
+    let (mint, payer, pid) = keys();
+    let first =
+        allocate_external_id_nonce(&mint, &payer, &pid, b"employee-5282", |_| false).unwrap();
+    let second = allocate_external_id_nonce(&mint, &payer, &pid, b"employee-49268", |m| {
+        *m == first.metadata
+    });
+    assert_eq!(
+        second,
+        Err(NonceError::Collision {
+            nonce,
+            metadata: first.metadata,
+        })
+    );
+}
+
+#[test]
+fn finds_free_nonce() {
+    let (mint, payer, pid) = keys();
+    let used: Vec<_> = (3..6)
+        .map(|nonce| AllocatedNonce::new(&mint, &payer, nonce, &pid).metadata)
+        .collect();
+
+    let mut checked = 0;
+    let free = find_free_nonce(&mint, &payer, &pid, 3, 10, |metadata| {
+        checked += 1;
+        used.contains(metadata)
+    })
+    .unwrap();
+    assert_eq!(free, AllocatedNonce::new(&mint, &payer, 6, &pid));
+    assert_eq!(checked, 4);
+    assert_eq!(
+        next_unused_nonce(&mint, &payer, &pid, 3, &used)
+            .unwrap()
+            .nonce,
+        6
+    );
+    assert_eq!(
+        next_unused_nonce(&mint, &payer, &pid, 0, &used)
+            .unwrap()
+            .nonce,
+        0
+    );
+}
+
+#[test]
+fn bounds_nonce_scan() {
+    let (mint, payer, pid) = keys();
+    let mut checked = 0;
+    let result = find_free_nonce(&mint, &payer, &pid, 0, 5, |_| {
+        checked += 1;
+        true
+    });
+    assert_eq!(result, Err(NonceError::Exhausted));
+    assert_eq!(checked, 5);
+
+    // the scan ends with the `u32` range
+    checked = 0;
+    let result = find_free_nonce(&mint, &payer, &pid, u32::MAX - 1, 5, |_| {
+        checked += 1;
+        true
+    });
+    assert_eq!(result, Err(NonceError::Exhausted));
+    assert_eq!(checked, 2);
+    assert_eq!(
+        find_free_nonce(&mint, &payer, &pid, 0, 0, |_| false),
+        Err(NonceError::Exhausted)
+    );
+}
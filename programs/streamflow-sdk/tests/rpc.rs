@@ -0,0 +1,202 @@
+#![cfg(feature = "client")]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use anchor_lang::prelude::*;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde_json::{json, Value};
+use solana_hash::Hash;
+use solana_keypair::Keypair;
+use solana_signer::Signer;
+use solana_transaction::Transaction;
+use streamflow_sdk::client::{ClientError, StreamflowClient, StreamflowRpc};
+use streamflow_sdk::rpc::HttpRpc;
+use streamflow_sdk::state::{Contract, METADATA_LEN};
+use streamflow_sdk::view::offset;
+
+const METADATA_V3: &[u8] = include_bytes!("fixtures/metadata_v3.bin");
+
+/// Requests received by `serve`
+type Requests = Arc<Mutex<Vec<Value>>>;
+
+/// JSON-RPC server answering every request with the members `respond` returns for its method
+/// and params, e.g. `{"result": ...}`
+fn serve(respond: fn(&str, &Value) -> Value) -> (HttpRpc, Requests) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Requests::default();
+    let received = requests.clone();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(&stream);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let request: Value = serde_json::from_slice(&body).unwrap();
+
+            let mut response = respond(request["method"].as_str().unwrap(), &request["params"]);
+            response["jsonrpc"] = json!("2.0");
+            response["id"] = request["id"].clone();
+            received.lock().unwrap().push(request);
+            let response = response.to_string();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+                 Connection: close\r\n\r\n{response}",
+                response.len()
+            )
+            .unwrap();
+        }
+    });
+    (HttpRpc::new(url), requests)
+}
+
+fn stream_account() -> Value {
+    json!({
+        "data": [BASE64_STANDARD.encode(METADATA_V3), "base64"],
+        "executable": false,
+        "lamports": 8_630_400,
+        "owner": streamflow_sdk::id().to_string(),
+        "rentEpoch": u64::MAX,
+        "space": METADATA_LEN,
+    })
+}
+
+fn fixture() -> Contract {
+    Contract::deserialize(&mut &METADATA_V3[..]).unwrap()
+}
+
+fn run<T>(future: impl std::future::Future<Output = T>) -> T {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+#[test]
+fn reads_streams() {
+    let (rpc, requests) = serve(|method, params| match method {
+        "getAccountInfo" if params[0] == streamflow_sdk::id().to_string() => {
+            json!({ "result": { "context": { "slot": 1 }, "value": null } })
+        }
+        "getAccountInfo" => {
+            json!({ "result": { "context": { "slot": 1 }, "value": stream_account() } })
+        }
+        "getProgramAccounts" => json!({ "result": [{
+            "pubkey": Pubkey::new_from_array([1; 32]).to_string(),
+            "account": stream_account(),
+        }] }),
+        _ => json!({ "error": { "code": -32601, "message": "Method not found" } }),
+    });
+    let client = StreamflowClient::new(rpc);
+    let contract = fixture();
+
+    let metadata = Pubkey::new_unique();
+    let stream = run(client.get_stream(&metadata)).unwrap();
+    assert_eq!(stream.sender, contract.sender);
+    assert!(matches!(
+        run(client.get_stream(&streamflow_sdk::id())),
+        Err(ClientError::AccountNotFound(_))
+    ));
+
+    let list = run(client.streams_by_sender(&contract.sender)).unwrap();
+    assert_eq!(list.streams.len(), 1);
+    assert_eq!(list.streams[0].0, Pubkey::new_from_array([1; 32]));
+    assert_eq!(list.streams[0].1.recipient, contract.recipient);
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(
+        requests[0]["params"],
+        json!([metadata.to_string(), { "encoding": "base64", "commitment": "confirmed" }])
+    );
+    assert_eq!(
+        requests[2]["params"][1]["filters"],
+        json!([
+            { "dataSize": METADATA_LEN },
+            { "memcmp": {
+                "offset": offset::SENDER,
+                "bytes": BASE64_STANDARD.encode(contract.sender),
+                "encoding": "base64",
+            } },
+        ])
+    );
+}
+
+#[test]
+fn sends_transactions() {
+    let blockhash = Hash::new_from_array([7; 32]);
+    let (rpc, requests) = serve(|method, params| match method {
+        "getLatestBlockhash" => json!({ "result": {
+            "context": { "slot": 1 },
+            "value": {
+                "blockhash": Hash::new_from_array([7; 32]).to_string(),
+                "lastValidBlockHeight": 1,
+            },
+        } }),
+        "getMinimumBalanceForRentExemption" => json!({ "result": params[0].as_u64().unwrap() * 2 }),
+        "sendTransaction" => {
+            let data = BASE64_STANDARD.decode(params[0].as_str().unwrap()).unwrap();
+            let tx: Transaction = bincode::deserialize(&data).unwrap();
+            json!({ "result": tx.signatures[0].to_string() })
+        }
+        "simulateTransaction" => json!({ "result": {
+            "context": { "slot": 1 },
+            "value": { "err": null, "logs": [], "unitsConsumed": 1_234 },
+        } }),
+        _ => json!({ "error": { "code": -32601, "message": "Method not found" } }),
+    });
+    assert_eq!(run(rpc.get_latest_blockhash()).unwrap(), blockhash);
+    assert_eq!(
+        run(rpc.get_minimum_balance_for_rent_exemption(METADATA_LEN)).unwrap(),
+        2 * METADATA_LEN as u64
+    );
+
+    let payer = Keypair::new();
+    let ix = streamflow_sdk::instructions::pause(payer.pubkey(), Pubkey::new_unique());
+    let tx = Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], blockhash);
+    assert_eq!(run(rpc.send_transaction(&tx)).unwrap(), tx.signatures[0]);
+    assert_eq!(run(rpc.simulate_compute_units(&tx)).unwrap(), 1_234);
+
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests[3]["params"][1]["sigVerify"], json!(false));
+    assert_eq!(
+        requests[3]["params"][1]["replaceRecentBlockhash"],
+        json!(true)
+    );
+}
+
+#[test]
+fn returns_rpc_errors() {
+    let (rpc, _) = serve(|method, _| match method {
+        "simulateTransaction" => json!({ "result": {
+            "context": { "slot": 1 },
+            "value": { "err": "AccountNotFound", "logs": [], "unitsConsumed": 0 },
+        } }),
+        _ => json!({ "error": { "code": -32005, "message": "Node is unhealthy" } }),
+    });
+    let error = run(rpc.get_latest_blockhash()).unwrap_err();
+    assert!(error.to_string().contains("Node is unhealthy"));
+
+    let payer = Keypair::new();
+    let ix = streamflow_sdk::instructions::pause(payer.pubkey(), Pubkey::new_unique());
+    let tx = Transaction::new_with_payer(&[ix], Some(&payer.pubkey()));
+    let error = run(rpc.simulate_compute_units(&tx)).unwrap_err();
+    assert!(error.to_string().contains("AccountNotFound"));
+}
@@ -0,0 +1,302 @@
+#[path = "../../streamflow-sdk-core/tests/common/mod.rs"]
+mod common;
+
+use common::{contract, DAY};
+use proptest::prelude::*;
+use streamflow_sdk::state::{calculate_fee_from_amount, Contract};
+use streamflow_sdk::update::UpdateArgs;
+
+/// `available_to_claim` multiplies unlocked amount by this percentage, 100% returns it as is
+const FULL: f32 = 100.0;
+
+prop_compose! {
+    fn arb_contract()(
+        start_time in 1_600_000_000u64..1_800_000_000,
+        net_amount_deposited in 1u64..1_000_000_000_000,
+        period in 1u64..31 * 86_400,
+        cliff_delay in prop::option::of(0u64..365 * 86_400),
+    )(
+        start_time in Just(start_time),
+        net_amount_deposited in Just(net_amount_deposited),
+        period in Just(period),
+        cliff_delay in Just(cliff_delay),
+        amount_per_period in 1..=net_amount_deposited,
+        cliff_amount in 0..net_amount_deposited,
+    ) -> Contract {
+        match cliff_delay {
+            Some(delay) => contract(
+                start_time,
+                net_amount_deposited,
+                period,
+                amount_per_period,
+                start_time + delay,
+                cliff_amount,
+            ),
+            None => contract(start_time, net_amount_deposited, period, amount_per_period, 0, 0),
+        }
+    }
+}
+
+/// Contract together with an offset from its start, may point past `end_time`
+fn arb_contract_at() -> impl Strategy<Value = (Contract, u64)> {
+    arb_contract().prop_flat_map(|c| {
+        let duration = c.end_time - c.start_time();
+        (Just(c), 0..=duration + duration / 4 + 1)
+    })
+}
+
+proptest! {
+    #[test]
+    fn unlocks_are_monotonic((c, offset) in arb_contract_at(), step in 0u64..10 * 86_400) {
+        let now = c.start_time() + offset;
+        prop_assert!(c.available_to_claim(now, FULL) <= c.available_to_claim(now + step, FULL));
+    }
+
+    #[test]
+    fn nothing_unlocks_before_start(c in arb_contract(), before in 1u64..1_000_000) {
+        prop_assert_eq!(c.available_to_claim(c.start_time().saturating_sub(before), FULL), 0);
+    }
+
+    #[test]
+    fn unlocks_never_exceed_deposit((mut c, offset) in arb_contract_at(), withdrawn_share in 0u64..=100) {
+        let now = c.start_time() + offset;
+        let available = c.available_to_claim(now, FULL);
+        prop_assert!(available <= c.ix.net_amount_deposited);
+
+        c.amount_withdrawn = (available as u128 * withdrawn_share as u128 / 100) as u64;
+        let left = c.available_to_claim(now, FULL);
+        prop_assert!(left + c.amount_withdrawn <= c.ix.net_amount_deposited);
+        prop_assert_eq!(left + c.amount_withdrawn, available);
+    }
+
+    #[test]
+    fn everything_unlocks_at_end(c in arb_contract(), after in 0u64..1_000_000) {
+        prop_assert_eq!(c.available_to_claim(c.end_time + after, FULL), c.ix.net_amount_deposited);
+    }
+
+    #[test]
+    fn active_pause_never_increases_availability(
+        (c, offset) in arb_contract_at(),
+        pause_share in 0u64..=100,
+    ) {
+        let now = c.start_time() + offset;
+        let mut paused = c.clone();
+        // a Stream can't be paused once it's fully unlocked
+        let last_pause_start = now.min(c.end_time - 1);
+        paused.current_pause_start =
+            c.created_at + (last_pause_start - c.created_at) * pause_share / 100;
+        prop_assert!(paused.available_to_claim(now, FULL) <= c.available_to_claim(now, FULL));
+    }
+
+    #[test]
+    fn past_pause_never_increases_availability(
+        (c, offset) in arb_contract_at(),
+        pause_cumulative in 0u64..30 * 86_400,
+    ) {
+        let now = c.start_time() + offset;
+        let mut paused = c.clone();
+        paused.pause_cumulative = pause_cumulative.min(offset);
+        paused.end_time += paused.pause_cumulative;
+        prop_assert!(paused.available_to_claim(now, FULL) <= c.available_to_claim(now, FULL));
+    }
+
+    #[test]
+    fn rate_change_preserves_unlocked_funds(
+        (c, offset) in arb_contract_at(),
+        new_rate in 1u64..1_000_000_000,
+        periods in 0u64..1_000,
+    ) {
+        let now = c.start_time() + offset;
+        let before = c.available_to_claim(now, FULL);
+        let updated = c.preview_update(UpdateArgs::new().set_rate(new_rate), now).unwrap().contract;
+
+        prop_assert_eq!(updated.available_to_claim(now, FULL), before);
+        // whole periods after the change unlock the new rate on top of the unlocked funds
+        let later = now + periods * c.ix.period;
+        prop_assert_eq!(
+            updated.available_to_claim(later, FULL),
+            (before + periods * new_rate).min(c.ix.net_amount_deposited)
+        );
+        prop_assert_eq!(
+            updated.available_to_claim(updated.end_time, FULL),
+            c.ix.net_amount_deposited
+        );
+    }
+
+    #[test]
+    fn schedule_matches_unlocks((c, offset) in arb_contract_at()) {
+        let now = c.start_time() + offset;
+        let mut unlocked = c.available_to_claim(now, FULL);
+        let mut last = now;
+        let mut finished = true;
+        for (i, unlock) in c.schedule(now).enumerate() {
+            if i == 1_000 {
+                finished = false;
+                break;
+            }
+            prop_assert!(unlock.time > last);
+            prop_assert_eq!(c.available_to_claim(unlock.time - 1, FULL), unlocked);
+            prop_assert_eq!(unlock.unlocked, c.available_to_claim(unlock.time, FULL));
+            prop_assert_eq!(unlock.amount, unlock.unlocked - unlocked);
+            unlocked = unlock.unlocked;
+            last = unlock.time;
+        }
+        if finished {
+            prop_assert_eq!(unlocked, c.ix.net_amount_deposited);
+        }
+    }
+
+    #[test]
+    fn lock_weight_matches_schedule(
+        (c, offset) in arb_contract_at(),
+        pause_cumulative in 0u64..30 * 86_400,
+        new_rate in prop::option::of(1u64..1_000_000_000),
+    ) {
+        let now = c.start_time() + offset;
+        let mut c = c;
+        c.pause_cumulative = pause_cumulative.min(offset);
+        c.end_time += c.pause_cumulative;
+        if let Some(rate) = new_rate {
+            c = c.preview_update(UpdateArgs::new().set_rate(rate), now).unwrap().contract;
+        }
+        // the reference sums every unlock, keep their number small
+        prop_assume!(c.end_time.saturating_sub(now) / c.ix.period < 100_000);
+        let expected: u128 = c
+            .schedule(now)
+            .map(|u| u128::from(u.amount) * u128::from(u.time - now))
+            .sum();
+        prop_assert_eq!(c.lock_weight(now), expected);
+        prop_assert_eq!(c.locked_at(now), c.ix.net_amount_deposited - c.unlocked_at(now));
+    }
+
+    #[test]
+    fn fee_is_within_one_unit_of_exact(amount in any::<u64>(), percentage in 0.0f32..=100.0) {
+        let fee = calculate_fee_from_amount(amount, percentage);
+        // the percentage is converted to an integer factor with 10^-6 precision using f32 math,
+        // so besides rounding the result may be off by one step of that factor
+        let exact = amount as f64 * percentage as f64 / 100.0;
+        let tolerance = 1.0 + amount as f64 / 1_000_000.0;
+        prop_assert!((fee as f64 - exact).abs() <= tolerance, "fee {} exact {}", fee, exact);
+        prop_assert!(fee <= amount);
+    }
+
+    #[test]
+    fn fee_of_exact_factor_is_within_one_unit(amount in any::<u64>(), bps in 0u32..=10_000) {
+        // percentages whose factor survives f32 conversion, e.g. 0.25%
+        let percentage = bps as f32 / 100.0;
+        let factor = (percentage / 100.0 * 1_000_000.0) as u128;
+        prop_assume!(factor == bps as u128 * 100);
+        let fee = calculate_fee_from_amount(amount, percentage);
+        let exact = amount as u128 * bps as u128 / 10_000;
+        prop_assert!(exact.abs_diff(fee as u128) <= 1);
+    }
+
+    #[test]
+    fn fee_is_zero_for_non_positive_percentage(amount in any::<u64>(), percentage in -100.0f32..=0.0) {
+        prop_assert_eq!(calculate_fee_from_amount(amount, percentage), 0);
+    }
+}
+
+/// Stream of the cancel test of the protocol program, quoted in the annex of `protocol_audit.pdf`:
+/// 20 tokens with 8 decimals, half of them at the cliff and 0.01 a second after, 0.25% fees.
+/// The test clock ended 183 seconds after the cliff, the program withdrew 11.83 tokens to the
+/// recipient and 0.029575 to the treasury and the partner each.
+#[test]
+fn protocol_cancel_vector() {
+    const NOW: u64 = 1_700_000_000;
+    const FEE_PERCENT: f32 = 0.25;
+    let mut c = contract(
+        NOW + 5,
+        2_000_000_000,
+        1,
+        1_000_000,
+        NOW + 40,
+        1_000_000_000,
+    );
+    c.streamflow_fee_percent = FEE_PERCENT;
+    c.partner_fee_percent = FEE_PERCENT;
+    // `strm_expected_fee_total` of the test
+    c.streamflow_fee_total = (0.0025 * 2_000_000_000f64) as u64;
+    c.partner_fee_total = c.streamflow_fee_total;
+    assert_eq!(
+        c.streamflow_fee_total,
+        calculate_fee_from_amount(2_000_000_000, FEE_PERCENT)
+    );
+
+    let cancel = c.preview_cancel(NOW + 40 + 183).unwrap();
+    assert_eq!(cancel.recipient_amount, 1_183_000_000);
+    assert_eq!(cancel.streamflow_fee, 2_957_500);
+    assert_eq!(cancel.partner_fee, 2_957_500);
+    assert_eq!(cancel.contract.amount_withdrawn, 1_183_000_000);
+}
+
+/// Fixed vectors guarding the current behavior, values are computed by hand
+#[test]
+fn hand_computed_vectors() {
+    const START: u64 = 1_700_000_000;
+
+    // linear stream, 1000 tokens per minute
+    let c = contract(START, 1_000_000, 60, 1_000, 0, 0);
+    assert_eq!(c.end_time, START + 60_000);
+    assert_eq!(c.available_to_claim(START - 1, FULL), 0);
+    assert_eq!(c.available_to_claim(START + 59, FULL), 0);
+    assert_eq!(c.available_to_claim(START + 60, FULL), 1_000);
+    assert_eq!(c.available_to_claim(START + 3_599, FULL), 59_000);
+    assert_eq!(c.available_to_claim(START + 60_000, FULL), 1_000_000);
+
+    // 1 year vesting with 25% cliff after 3 months and monthly unlocks
+    let cliff = START + 90 * DAY;
+    let c = contract(
+        START,
+        12_000_000_000,
+        30 * DAY,
+        1_000_000_000,
+        cliff,
+        3_000_000_000,
+    );
+    assert_eq!(c.end_time, cliff + 9 * 30 * DAY);
+    assert_eq!(c.available_to_claim(cliff - 1, FULL), 0);
+    assert_eq!(c.available_to_claim(cliff, FULL), 3_000_000_000);
+    assert_eq!(c.available_to_claim(cliff + 30 * DAY, FULL), 4_000_000_000);
+    assert_eq!(c.available_to_claim(cliff + 95 * DAY, FULL), 6_000_000_000);
+
+    // last period unlocks only the remainder
+    let c = contract(START, 1_050, 10, 100, 0, 0);
+    assert_eq!(c.end_time, START + 110);
+    assert_eq!(c.available_to_claim(START + 109, FULL), 1_000);
+    assert_eq!(c.available_to_claim(START + 110, FULL), 1_050);
+
+    // paused on day 10 and still paused on day 20
+    let mut c = contract(START, 100 * DAY, 1, 1, 0, 0);
+    c.current_pause_start = START + 10 * DAY;
+    assert_eq!(c.available_to_claim(START + 20 * DAY, FULL), 10 * DAY);
+    // paused before the start, nothing unlocks until unpaused
+    c.current_pause_start = START - 1;
+    assert_eq!(c.available_to_claim(START + 20 * DAY, FULL), 0);
+    // paused for 5 days in total, now unpaused
+    c.current_pause_start = 0;
+    c.pause_cumulative = 5 * DAY;
+    c.end_time += 5 * DAY;
+    assert_eq!(c.available_to_claim(START + 20 * DAY, FULL), 15 * DAY);
+
+    // rate doubled on day 10
+    let c = contract(START, 100 * DAY, 1, 1, 0, 0);
+    let c = c
+        .preview_update(UpdateArgs::new().set_rate(2), START + 10 * DAY)
+        .unwrap()
+        .contract;
+    assert_eq!(c.funds_unlocked_at_last_rate_change, 10 * DAY);
+    assert_eq!(c.end_time, START + 55 * DAY);
+    assert_eq!(c.available_to_claim(START + 20 * DAY, FULL), 30 * DAY);
+
+    // fees
+    assert_eq!(calculate_fee_from_amount(1_000_000_000, 0.25), 2_500_000);
+    assert_eq!(calculate_fee_from_amount(1_000_000, 0.5), 5_000);
+    assert_eq!(calculate_fee_from_amount(1_000_000, 100.0), 1_000_000);
+    // factor of 0.7% is truncated to 0.6999% by f32 math
+    assert_eq!(calculate_fee_from_amount(1_000_000, 0.7), 6_999);
+    assert_eq!(
+        calculate_fee_from_amount(u64::MAX, 0.25),
+        46_116_860_184_273_879
+    );
+}